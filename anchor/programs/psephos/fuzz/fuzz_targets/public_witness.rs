@@ -0,0 +1,23 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use psephos::groth16::is_canonical_scalar;
+use psephos::witness::PublicWitness;
+use psephos::{FIELD_ELEMENT_SIZE, PUBLIC_WITNESS_HEADER_SIZE};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(witness) = PublicWitness::parse(data) {
+        // Accepted witnesses are exactly header + elements, all canonical
+        assert_eq!(data.len(), PUBLIC_WITNESS_HEADER_SIZE + witness.len() * FIELD_ELEMENT_SIZE);
+        assert!(witness.elements().iter().all(is_canonical_scalar));
+
+        for index in 0..witness.len() {
+            let element = witness.field(index).unwrap();
+            if let Ok(value) = witness.u64_at(index) {
+                assert_eq!(element[FIELD_ELEMENT_SIZE - 8..], value.to_be_bytes());
+                assert!(element[..FIELD_ELEMENT_SIZE - 8].iter().all(|byte| *byte == 0));
+            }
+        }
+        assert!(witness.field(witness.len()).is_err());
+    }
+});
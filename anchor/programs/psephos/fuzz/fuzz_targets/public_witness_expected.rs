@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use psephos::witness::PublicWitness;
+
+fuzz_target!(|input: (u8, &[u8])| {
+    let (expected_inputs, data) = input;
+    let expected_inputs = expected_inputs as usize % 8;
+
+    if let Ok(witness) = PublicWitness::parse_expected(data, expected_inputs) {
+        assert_eq!(witness.len(), expected_inputs);
+        assert_eq!(PublicWitness::parse(data), Ok(witness));
+    }
+});
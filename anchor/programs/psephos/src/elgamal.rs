@@ -0,0 +1,392 @@
+//! Exponential ElGamal ballots over BN254 G1 with threshold decryption
+//!
+//! Encrypted proposals replace commit-reveal with homomorphic tallying. A
+//! ballot for option `j` is one ciphertext encrypting `m = 2^(24 * j)`:
+//!
+//! ```text
+//! C1 = k * G,  C2 = m * G + k * H
+//! ```
+//!
+//! where `H` is the trustees' joint election key. Ciphertexts add
+//! component-wise, so the aggregate encrypts `sum_j tally_j * 2^(24 * j)` and
+//! each option's count sits in its own 24-bit slot. Points use the EIP-197
+//! encoding of the alt_bn128 syscalls, with all-zero bytes as the identity.
+//!
+//! The election secret `x` is Shamir-shared among the trustees: trustee `i`
+//! (evaluation point `i + 1`) holds `x_i` and publishes `Y_i = x_i * G`.
+//! After voting each trustee posts `D_i = x_i * C1` with a Chaum-Pedersen
+//! (DLEQ) proof. Any `threshold` shares interpolate in the exponent to
+//! `x * C1`, and claimed tallies are accepted only if
+//! `C2 - x * C1 == packed_tallies * G`, so nobody has to solve a discrete log
+//! on-chain.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::alt_bn128::prelude::{alt_bn128_addition, alt_bn128_multiplication};
+use anchor_lang::solana_program::hash::hashv as sha256v;
+use anchor_lang::solana_program::poseidon::{hashv, Endianness, Parameters};
+
+use crate::groth16::{is_canonical_scalar, negate_g1, G1_SIZE};
+use crate::{u64_to_field, PsephosError};
+
+/// Width of each option's slot in the packed plaintext
+pub const TALLY_SLOT_BITS: usize = 24;
+/// Largest count a slot can hold without overflowing into the next option
+pub const MAX_SLOT_TALLY: u64 = (1 << TALLY_SLOT_BITS) - 1;
+
+/// BN254 G1 generator (1, 2)
+pub const G1_GENERATOR: [u8; G1_SIZE] = {
+    let mut generator = [0u8; G1_SIZE];
+    generator[31] = 1;
+    generator[63] = 2;
+    generator
+};
+
+/// G1 identity in the syscall encoding
+pub const G1_IDENTITY: [u8; G1_SIZE] = [0u8; G1_SIZE];
+
+/// Domain separator for the DLEQ challenge hash
+const DLEQ_DOMAIN: &[u8] = b"psephos-dleq-v1";
+
+/// An exponential ElGamal ciphertext (C1, C2)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct Ciphertext {
+    pub c1: [u8; G1_SIZE],
+    pub c2: [u8; G1_SIZE],
+}
+
+impl Ciphertext {
+    /// Encryption of zero with zero randomness
+    pub const IDENTITY: Self = Self { c1: G1_IDENTITY, c2: G1_IDENTITY };
+
+    /// Homomorphically add two ciphertexts, rejecting points off the curve
+    pub fn add(&self, other: &Self) -> Result<Self> {
+        Ok(Self {
+            c1: add(&self.c1, &other.c1)?,
+            c2: add(&self.c2, &other.c2)?,
+        })
+    }
+}
+
+/// P + Q
+pub fn add(p: &[u8; G1_SIZE], q: &[u8; G1_SIZE]) -> Result<[u8; G1_SIZE]> {
+    let sum = alt_bn128_addition(&[p.as_slice(), q.as_slice()].concat())
+        .map_err(|_| PsephosError::InvalidCiphertext)?;
+    sum.try_into().map_err(|_| PsephosError::InvalidCiphertext.into())
+}
+
+/// scalar * P
+pub fn mul(p: &[u8; G1_SIZE], scalar: &[u8; 32]) -> Result<[u8; G1_SIZE]> {
+    let product = alt_bn128_multiplication(&[p.as_slice(), scalar.as_slice()].concat())
+        .map_err(|_| PsephosError::InvalidCiphertext)?;
+    product.try_into().map_err(|_| PsephosError::InvalidCiphertext.into())
+}
+
+/// Check that `p` is the identity or a point on the curve
+pub fn validate_point(p: &[u8; G1_SIZE]) -> Result<()> {
+    add(p, &G1_IDENTITY).map(|_| ())
+}
+
+/// Split a 32-byte coordinate into (high, low) 128-bit field elements
+///
+/// Base field coordinates can exceed the scalar modulus, so they are hashed
+/// as two halves that always fit in a BN254 scalar.
+pub fn split_coordinate(coordinate: &[u8]) -> ([u8; 32], [u8; 32]) {
+    let mut high = [0u8; 32];
+    let mut low = [0u8; 32];
+    high[16..].copy_from_slice(&coordinate[..16]);
+    low[16..].copy_from_slice(&coordinate[16..32]);
+    (high, low)
+}
+
+/// Split a point into its four 128-bit halves (x_hi, x_lo, y_hi, y_lo)
+fn point_halves(point: &[u8; G1_SIZE]) -> [[u8; 32]; 4] {
+    let (x_high, x_low) = split_coordinate(&point[..32]);
+    let (y_high, y_low) = split_coordinate(&point[32..]);
+    [x_high, x_low, y_high, y_low]
+}
+
+/// poseidon(H.x_hi, H.x_lo, H.y_hi, H.y_lo, num_options)
+///
+/// Binds a ballot to the election key and the option count the circuit
+/// range-checked the choice against.
+pub fn key_hash(election_key: &[u8; G1_SIZE], num_options: u8) -> Result<[u8; 32]> {
+    let halves = point_halves(election_key);
+    let num_options = u64_to_field(num_options as u64);
+    let hash = hashv(
+        Parameters::Bn254X5,
+        Endianness::BigEndian,
+        &[&halves[0], &halves[1], &halves[2], &halves[3], &num_options],
+    )
+    .map_err(|_| PsephosError::InvalidCiphertext)?;
+    Ok(hash.to_bytes())
+}
+
+/// poseidon(key_hash, C1.x_hi, C1.x_lo, C1.y_hi, C1.y_lo, C2.x_hi, ...)
+///
+/// The ballot hash takes the vote commitment's place among the voting
+/// circuit's public inputs.
+pub fn ballot_hash(key_hash: &[u8; 32], ciphertext: &Ciphertext) -> Result<[u8; 32]> {
+    let c1 = point_halves(&ciphertext.c1);
+    let c2 = point_halves(&ciphertext.c2);
+    let hash = hashv(
+        Parameters::Bn254X5,
+        Endianness::BigEndian,
+        &[key_hash, &c1[0], &c1[1], &c1[2], &c1[3], &c2[0], &c2[1], &c2[2], &c2[3]],
+    )
+    .map_err(|_| PsephosError::InvalidCiphertext)?;
+    Ok(hash.to_bytes())
+}
+
+/// Fiat-Shamir challenge for a DLEQ proof, reduced below the scalar modulus
+/// by clearing the top three bits
+pub fn dleq_challenge(
+    proposal: &Pubkey,
+    trustee_key: &[u8; G1_SIZE],
+    base: &[u8; G1_SIZE],
+    share: &[u8; G1_SIZE],
+    commitment_g: &[u8; G1_SIZE],
+    commitment_base: &[u8; G1_SIZE],
+) -> [u8; 32] {
+    let mut challenge = sha256v(&[
+        DLEQ_DOMAIN,
+        proposal.as_ref(),
+        trustee_key,
+        base,
+        share,
+        commitment_g,
+        commitment_base,
+    ])
+    .to_bytes();
+    challenge[0] &= 0x1f;
+    challenge
+}
+
+/// Verify that `log_G(trustee_key) == log_base(share)`
+///
+/// The proof is `(challenge, response)`; the prover's commitments are
+/// recovered as `response * G - challenge * Y` and
+/// `response * base - challenge * D` and must hash back to `challenge`.
+pub fn verify_dleq(
+    proposal: &Pubkey,
+    trustee_key: &[u8; G1_SIZE],
+    base: &[u8; G1_SIZE],
+    share: &[u8; G1_SIZE],
+    challenge: &[u8; 32],
+    response: &[u8; 32],
+) -> Result<()> {
+    require!(is_canonical_scalar(response), PsephosError::InvalidDecryptionShare);
+
+    let commitment_g = add(
+        &mul(&G1_GENERATOR, response)?,
+        &negate_g1(&mul(trustee_key, challenge)?),
+    )?;
+    let commitment_base = add(&mul(base, response)?, &negate_g1(&mul(share, challenge)?))?;
+
+    let expected = dleq_challenge(proposal, trustee_key, base, share, &commitment_g, &commitment_base);
+    require!(expected == *challenge, PsephosError::InvalidDecryptionShare);
+    Ok(())
+}
+
+/// A trustee's decryption share prepared for interpolation
+pub struct ShareInput {
+    /// Shamir evaluation point (trustee index + 1)
+    pub x: u64,
+    /// D_i = x_i * C1, as verified by its DLEQ proof
+    pub share: [u8; G1_SIZE],
+    /// D_i divided by prod_{j != i} |x_j - x_i|, supplied by the caller
+    pub scaled_share: [u8; G1_SIZE],
+}
+
+/// Lagrange-interpolate decryption shares in the exponent to `x * C1`
+///
+/// The coefficient for share `i` is `prod_{j != i} x_j / (x_j - x_i)`.
+/// Instead of inverting the denominator on-chain, the caller supplies
+/// `scaled_share = D_i / |denominator|`; it is checked by multiplying back,
+/// which pins it uniquely because the group order is prime.
+pub fn combine_shares(shares: &[ShareInput]) -> Result<[u8; G1_SIZE]> {
+    let mut combined = G1_IDENTITY;
+    for (i, share) in shares.iter().enumerate() {
+        let mut numerator: u64 = 1;
+        let mut denominator: u64 = 1;
+        let mut negative = false;
+        for (j, other) in shares.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+            require!(other.x != share.x, PsephosError::InvalidDecryptionShare);
+            numerator = numerator.checked_mul(other.x).ok_or(PsephosError::InvalidDecryptionShare)?;
+            denominator = denominator
+                .checked_mul(other.x.abs_diff(share.x))
+                .ok_or(PsephosError::InvalidDecryptionShare)?;
+            negative ^= other.x < share.x;
+        }
+
+        require!(
+            mul(&share.scaled_share, &u64_to_field(denominator))? == share.share,
+            PsephosError::InvalidDecryptionShare
+        );
+
+        let mut term = mul(&share.scaled_share, &u64_to_field(numerator))?;
+        if negative {
+            term = negate_g1(&term);
+        }
+        combined = add(&combined, &term)?;
+    }
+    Ok(combined)
+}
+
+/// Pack per-option tallies into the scalar `sum_j tally_j * 2^(24 * j)`
+pub fn pack_tallies(tallies: &[u64]) -> Result<[u8; 32]> {
+    const SLOT_BYTES: usize = TALLY_SLOT_BITS / 8;
+    require!(tallies.len() * SLOT_BYTES <= 30, PsephosError::TallyDecryptionFailed);
+
+    let mut packed = [0u8; 32];
+    for (option, tally) in tallies.iter().enumerate() {
+        require!(*tally <= MAX_SLOT_TALLY, PsephosError::TallyDecryptionFailed);
+        let end = 32 - option * SLOT_BYTES;
+        packed[end - SLOT_BYTES..end].copy_from_slice(&tally.to_be_bytes()[8 - SLOT_BYTES..]);
+    }
+    Ok(packed)
+}
+
+/// Check claimed tallies against the aggregate and the combined decryption
+/// factor: `C2 - x * C1 == packed_tallies * G`
+pub fn verify_tallies(aggregate: &Ciphertext, decryption_factor: &[u8; G1_SIZE], tallies: &[u64]) -> Result<()> {
+    let plaintext = add(&aggregate.c2, &negate_g1(decryption_factor))?;
+    let expected = mul(&G1_GENERATOR, &pack_tallies(tallies)?)?;
+    require!(plaintext == expected, PsephosError::TallyDecryptionFailed);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+    use ark_ff::{BigInteger, Field, PrimeField};
+
+    fn scalar(value: Fr) -> [u8; 32] {
+        value.into_bigint().to_bytes_be().try_into().unwrap()
+    }
+
+    fn from_scalar(bytes: &[u8; 32]) -> Fr {
+        Fr::from_be_bytes_mod_order(bytes)
+    }
+
+    fn point(value: Fr) -> [u8; 64] {
+        mul(&G1_GENERATOR, &scalar(value)).unwrap()
+    }
+
+    fn encrypt(key: &[u8; 64], choice: usize, randomness: u64) -> Ciphertext {
+        let k = scalar(Fr::from(randomness));
+        let message = mul(&G1_GENERATOR, &scalar(Fr::from(2u64).pow([(TALLY_SLOT_BITS * choice) as u64]))).unwrap();
+        Ciphertext {
+            c1: mul(&G1_GENERATOR, &k).unwrap(),
+            c2: add(&message, &mul(key, &k).unwrap()).unwrap(),
+        }
+    }
+
+    fn prove_dleq(proposal: &Pubkey, secret: Fr, base: &[u8; 64], nonce: u64) -> ([u8; 64], [u8; 32], [u8; 32]) {
+        let trustee_key = point(secret);
+        let share = mul(base, &scalar(secret)).unwrap();
+        let w = Fr::from(nonce);
+        let commitment_g = point(w);
+        let commitment_base = mul(base, &scalar(w)).unwrap();
+        let challenge = dleq_challenge(proposal, &trustee_key, base, &share, &commitment_g, &commitment_base);
+        let response = w + from_scalar(&challenge) * secret;
+        (share, challenge, scalar(response))
+    }
+
+    /// Shamir shares of `secret` on the line f(z) = secret + 7 * z
+    fn trustee_secret(secret: u64, x: u64) -> Fr {
+        Fr::from(secret + 7 * x)
+    }
+
+    fn scaled(share: &[u8; 64], x: u64, participants: &[u64]) -> [u8; 64] {
+        let denominator: u64 = participants.iter().filter(|other| **other != x).map(|other| other.abs_diff(x)).product();
+        mul(share, &scalar(Fr::from(denominator).inverse().unwrap())).unwrap()
+    }
+
+    #[test]
+    fn decrypts_aggregated_ballots_with_threshold_shares() {
+        let proposal = Pubkey::new_unique();
+        let secret = 42u64;
+        let election_key = point(Fr::from(secret));
+
+        let mut aggregate = Ciphertext::IDENTITY;
+        for (choice, randomness) in [(0, 11), (2, 12), (2, 13), (1, 14), (2, 15)] {
+            aggregate = aggregate.add(&encrypt(&election_key, choice, randomness)).unwrap();
+        }
+
+        // 2-of-3: trustees at x = 1 and x = 3 decrypt
+        let participants = [1u64, 3];
+        let shares: Vec<ShareInput> = participants
+            .iter()
+            .map(|x| {
+                let secret_share = trustee_secret(secret, *x);
+                let (share, challenge, response) = prove_dleq(&proposal, secret_share, &aggregate.c1, 99 + x);
+                verify_dleq(&proposal, &point(secret_share), &aggregate.c1, &share, &challenge, &response).unwrap();
+                ShareInput { x: *x, share, scaled_share: scaled(&share, *x, &participants) }
+            })
+            .collect();
+
+        let factor = combine_shares(&shares).unwrap();
+        assert!(verify_tallies(&aggregate, &factor, &[1, 1, 3]).is_ok());
+        assert!(verify_tallies(&aggregate, &factor, &[2, 0, 3]).is_err());
+    }
+
+    #[test]
+    fn rejects_dleq_for_wrong_share() {
+        let proposal = Pubkey::new_unique();
+        let base = point(Fr::from(5u64));
+        let secret = Fr::from(9u64);
+        let (_, challenge, response) = prove_dleq(&proposal, secret, &base, 3);
+
+        let forged = mul(&base, &scalar(Fr::from(10u64))).unwrap();
+        assert!(verify_dleq(&proposal, &point(secret), &base, &forged, &challenge, &response).is_err());
+    }
+
+    #[test]
+    fn rejects_dleq_replayed_on_another_proposal() {
+        let base = point(Fr::from(5u64));
+        let secret = Fr::from(9u64);
+        let (share, challenge, response) = prove_dleq(&Pubkey::new_unique(), secret, &base, 3);
+
+        let other = Pubkey::new_unique();
+        assert!(verify_dleq(&other, &point(secret), &base, &share, &challenge, &response).is_err());
+    }
+
+    #[test]
+    fn rejects_wrong_scaled_share() {
+        let share = point(Fr::from(8u64));
+        let shares = [
+            ShareInput { x: 1, share, scaled_share: share },
+            ShareInput { x: 3, share, scaled_share: share },
+        ];
+        // |3 - 1| = 2, so the scaled share must be D / 2, not D
+        assert!(combine_shares(&shares).is_err());
+    }
+
+    #[test]
+    fn packs_tallies_into_slots() {
+        let packed = pack_tallies(&[1, 2, 3]).unwrap();
+        assert_eq!(&packed[23..], &[0, 0, 3, 0, 0, 2, 0, 0, 1]);
+        assert!(pack_tallies(&[MAX_SLOT_TALLY + 1]).is_err());
+        assert!(pack_tallies(&[0; 11]).is_err());
+    }
+
+    #[test]
+    fn empty_aggregate_decrypts_to_zero() {
+        let factor = combine_shares(&[]).unwrap();
+        assert!(verify_tallies(&Ciphertext::IDENTITY, &factor, &[0, 0]).is_ok());
+    }
+
+    #[test]
+    fn ballot_hash_binds_ciphertext() {
+        let key = point(Fr::from(42u64));
+        let key_hash = key_hash(&key, 3).unwrap();
+        let first = ballot_hash(&key_hash, &encrypt(&key, 0, 1)).unwrap();
+        let second = ballot_hash(&key_hash, &encrypt(&key, 1, 1)).unwrap();
+        assert_ne!(first, second);
+        assert_ne!(key_hash, super::key_hash(&key, 2).unwrap());
+    }
+}
@@ -0,0 +1,507 @@
+//! Native Groth16 verification over BN254 using Solana's alt_bn128 syscalls
+//!
+//! Used instead of a CPI when a proposal pins a `VerifyingKey` account rather
+//! than a Sunspot verifier program. Proofs are read in gnark's uncompressed
+//! Groth16 format; points are passed to the syscalls in EIP-197 encoding
+//! (big-endian, G2 coordinates as imaginary || real), which gnark already uses.
+//!
+//! Proofs may carry one BSB22 commitment, as every Noir circuit compiled by
+//! Sunspot does. As in gnark's verifier, the commitment is hashed together
+//! with the public inputs it commits to into one extra public input, added
+//! to vk_x, and its Pedersen proof of knowledge is checked against the
+//! commitment key stored with the verifying key.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::alt_bn128::prelude::{
+    alt_bn128_addition, alt_bn128_multiplication, alt_bn128_pairing,
+};
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::{PsephosError, VerifyingKey};
+
+/// Size of an uncompressed G1 point (X || Y)
+pub const G1_SIZE: usize = 64;
+/// Size of an uncompressed G2 point (X.A1 || X.A0 || Y.A1 || Y.A0)
+pub const G2_SIZE: usize = 128;
+/// Size of a gnark proof without the BSB22 commitment section (Ar || Bs || Krs)
+pub const GNARK_PROOF_POINTS_SIZE: usize = G1_SIZE + G2_SIZE + G1_SIZE;
+/// Size of a gnark proof with an empty commitment list (count + proof of knowledge)
+pub const GNARK_PROOF_NO_COMMITMENT_SIZE: usize = GNARK_PROOF_POINTS_SIZE + 4 + G1_SIZE;
+/// Size of a gnark proof with one commitment
+pub const GNARK_PROOF_ONE_COMMITMENT_SIZE: usize = GNARK_PROOF_NO_COMMITMENT_SIZE + G1_SIZE;
+
+/// Domain separation tag gnark hashes BSB22 commitments under
+pub const COMMITMENT_DST: &[u8] = b"bsb22-commitment";
+/// Bytes expanded per hashed field element (gnark `fr.Hash`: 16 + 32)
+const HASH_TO_FIELD_BYTES: usize = 48;
+
+/// BN254 base field modulus q, big-endian
+const BASE_FIELD_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x97, 0x81, 0x6a, 0x91, 0x68, 0x71, 0xca, 0x8d, 0x3c, 0x20, 0x8c, 0x16, 0xd8, 0x7c, 0xfd, 0x47,
+];
+
+/// BN254 scalar field modulus r, big-endian
+pub const SCALAR_FIELD_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x28, 0x33, 0xe8, 0x48, 0x79, 0xb9, 0x70, 0x91, 0x43, 0xe1, 0xf5, 0x93, 0xf0, 0x00, 0x00, 0x01,
+];
+
+/// gnark flag bits in the most significant byte of a point's X coordinate
+const GNARK_FLAG_MASK: u8 = 0b1100_0000;
+const GNARK_UNCOMPRESSED_INFINITY: u8 = 0b0100_0000;
+
+/// A Groth16 proof as G1/G2 points ready for the alt_bn128 syscalls
+pub struct Groth16Proof {
+    pub a: [u8; G1_SIZE],
+    pub b: [u8; G2_SIZE],
+    pub c: [u8; G1_SIZE],
+    pub commitment: Option<ProofCommitment>,
+}
+
+/// A BSB22 commitment and its Pedersen proof of knowledge
+pub struct ProofCommitment {
+    pub point: [u8; G1_SIZE],
+    pub pok: [u8; G1_SIZE],
+}
+
+impl Groth16Proof {
+    /// Parse a gnark Groth16 proof (`WriteRawTo` format)
+    ///
+    /// Accepts `Ar || Bs || Krs`, optionally followed by a commitment count,
+    /// the commitments and their proof of knowledge. At most one commitment
+    /// is supported.
+    pub fn from_gnark(bytes: &[u8]) -> Result<Self> {
+        let count = match bytes.len() {
+            GNARK_PROOF_POINTS_SIZE => 0,
+            len if len >= GNARK_PROOF_POINTS_SIZE + 4 => {
+                let count_bytes: [u8; 4] = bytes[GNARK_PROOF_POINTS_SIZE..GNARK_PROOF_POINTS_SIZE + 4]
+                    .try_into()
+                    .map_err(|_| PsephosError::InvalidProof)?;
+                let count = u32::from_be_bytes(count_bytes);
+                require!(count <= 1, PsephosError::UnsupportedProofCommitment);
+                let expected = if count == 0 {
+                    GNARK_PROOF_NO_COMMITMENT_SIZE
+                } else {
+                    GNARK_PROOF_ONE_COMMITMENT_SIZE
+                };
+                require!(len == expected, PsephosError::InvalidProof);
+                count
+            }
+            _ => return err!(PsephosError::InvalidProof),
+        };
+
+        let a = parse_g1(&bytes[..G1_SIZE])?;
+        let b = parse_g2(&bytes[G1_SIZE..G1_SIZE + G2_SIZE])?;
+        let c = parse_g1(&bytes[G1_SIZE + G2_SIZE..GNARK_PROOF_POINTS_SIZE])?;
+
+        // Count || commitment || proof of knowledge
+        let commitment = if count == 1 {
+            let start = GNARK_PROOF_POINTS_SIZE + 4;
+            Some(ProofCommitment {
+                point: parse_g1(&bytes[start..start + G1_SIZE])?,
+                pok: parse_g1(&bytes[start + G1_SIZE..start + 2 * G1_SIZE])?,
+            })
+        } else {
+            None
+        };
+
+        Ok(Self { a, b, c, commitment })
+    }
+}
+
+/// Verify a Groth16 proof against a stored verifying key
+///
+/// Checks e(-A, B) * e(alpha, beta) * e(vk_x, gamma) * e(C, delta) == 1
+/// where vk_x = IC[0] + sum(input_i * IC[i + 1]).
+///
+/// With a commitment D, its proof of knowledge P is checked first with
+/// e(D, G) * e(P, G_root_sigma_neg) == 1; then the hash of D and its
+/// committed public inputs is appended to the public inputs and D is added
+/// to vk_x.
+pub fn verify(vk: &VerifyingKey, proof: &Groth16Proof, public_inputs: &[[u8; 32]]) -> Result<()> {
+    for input in public_inputs {
+        require!(is_canonical_scalar(input), PsephosError::InvalidPublicWitness);
+    }
+
+    let mut inputs = public_inputs.to_vec();
+    match (&vk.commitment_key, &proof.commitment) {
+        (None, None) => {}
+        (Some(key), Some(commitment)) => {
+            let pok_input = [
+                commitment.point.as_slice(),
+                key.g.as_slice(),
+                commitment.pok.as_slice(),
+                key.g_root_sigma_neg.as_slice(),
+            ]
+            .concat();
+            pairing_check(&pok_input)?;
+
+            let mut prehash = commitment.point.to_vec();
+            for index in &key.committed_inputs {
+                let input = public_inputs
+                    .get(*index as usize)
+                    .ok_or(PsephosError::InvalidPublicWitness)?;
+                prehash.extend_from_slice(input);
+            }
+            inputs.push(hash_to_field(&prehash, COMMITMENT_DST));
+        }
+        _ => return err!(PsephosError::UnsupportedProofCommitment),
+    }
+    require!(inputs.len() + 1 == vk.ic.len(), PsephosError::InvalidPublicWitness);
+
+    let mut vk_x = vk.ic[0];
+    for (input, ic) in inputs.iter().zip(&vk.ic[1..]) {
+        let term = alt_bn128_multiplication(&[ic.as_slice(), input.as_slice()].concat())
+            .map_err(|_| PsephosError::ProofVerificationFailed)?;
+        vk_x = add_g1(&vk_x, &term)?;
+    }
+    if let Some(commitment) = &proof.commitment {
+        vk_x = add_g1(&vk_x, &commitment.point)?;
+    }
+
+    let pairing_input = [
+        negate_g1(&proof.a).as_slice(),
+        proof.b.as_slice(),
+        vk.alpha_g1.as_slice(),
+        vk.beta_g2.as_slice(),
+        vk_x.as_slice(),
+        vk.gamma_g2.as_slice(),
+        proof.c.as_slice(),
+        vk.delta_g2.as_slice(),
+    ]
+    .concat();
+    pairing_check(&pairing_input)
+}
+
+/// Add two G1 points
+fn add_g1(a: &[u8], b: &[u8]) -> Result<[u8; G1_SIZE]> {
+    let sum = alt_bn128_addition(&[a, b].concat()).map_err(|_| PsephosError::ProofVerificationFailed)?;
+    sum.try_into().map_err(|_| PsephosError::ProofVerificationFailed.into())
+}
+
+/// Check that the product of the pairings of (G1, G2) pairs in `input` is 1
+fn pairing_check(input: &[u8]) -> Result<()> {
+    let result = alt_bn128_pairing(input).map_err(|_| PsephosError::ProofVerificationFailed)?;
+    require!(
+        result.len() == 32 && result[31] == 1 && result[..31].iter().all(|byte| *byte == 0),
+        PsephosError::ProofVerificationFailed
+    );
+    Ok(())
+}
+
+/// Hash `msg` to a scalar field element like gnark's `fr.Hash`:
+/// expand_message_xmd with SHA-256 to 48 bytes, reduced mod r
+pub fn hash_to_field(msg: &[u8], dst: &[u8]) -> [u8; 32] {
+    reduce_scalar(&expand_message_xmd(msg, dst, HASH_TO_FIELD_BYTES))
+}
+
+/// RFC 9380 expand_message_xmd with SHA-256; `len` is at most 255 blocks
+pub fn expand_message_xmd(msg: &[u8], dst: &[u8], len: usize) -> Vec<u8> {
+    let dst_len = [dst.len() as u8];
+    // Z_pad is one SHA-256 block of zeros
+    let b0 = hashv(&[&[0u8; 64], msg, &(len as u16).to_be_bytes(), &[0], dst, &dst_len]).to_bytes();
+
+    let mut out = Vec::with_capacity(len.div_ceil(32) * 32);
+    let mut block = hashv(&[&b0, &[1], dst, &dst_len]).to_bytes();
+    out.extend_from_slice(&block);
+    for i in 2..=len.div_ceil(32) {
+        let mixed: Vec<u8> = b0.iter().zip(&block).map(|(x, y)| x ^ y).collect();
+        block = hashv(&[&mixed, &[i as u8], dst, &dst_len]).to_bytes();
+        out.extend_from_slice(&block);
+    }
+    out.truncate(len);
+    out
+}
+
+/// Reduce a big-endian integer modulo r, one bit at a time
+pub fn reduce_scalar(bytes: &[u8]) -> [u8; 32] {
+    // Little-endian u64 limbs; the accumulator stays below r < 2^254
+    let modulus = limbs(&SCALAR_FIELD_MODULUS);
+    let mut acc = [0u64; 4];
+    for byte in bytes {
+        for bit in (0..8).rev() {
+            let mut carry = ((byte >> bit) & 1) as u64;
+            for limb in acc.iter_mut() {
+                let next = *limb >> 63;
+                *limb = (*limb << 1) | carry;
+                carry = next;
+            }
+            if !less_than(&acc, &modulus) {
+                let mut borrow = 0u64;
+                for (limb, m) in acc.iter_mut().zip(&modulus) {
+                    let (diff, b1) = limb.overflowing_sub(*m);
+                    let (diff, b2) = diff.overflowing_sub(borrow);
+                    *limb = diff;
+                    borrow = (b1 || b2) as u64;
+                }
+            }
+        }
+    }
+
+    let mut out = [0u8; 32];
+    for (i, limb) in acc.iter().enumerate() {
+        out[24 - 8 * i..32 - 8 * i].copy_from_slice(&limb.to_be_bytes());
+    }
+    out
+}
+
+/// Little-endian u64 limbs of a big-endian 32-byte value
+fn limbs(value: &[u8; 32]) -> [u64; 4] {
+    let mut limbs = [0u64; 4];
+    for (i, limb) in limbs.iter_mut().enumerate() {
+        *limb = u64::from_be_bytes(value[24 - 8 * i..32 - 8 * i].try_into().unwrap());
+    }
+    limbs
+}
+
+fn less_than(a: &[u64; 4], b: &[u64; 4]) -> bool {
+    a.iter().rev().cmp(b.iter().rev()) == std::cmp::Ordering::Less
+}
+
+/// Whether a big-endian 32-byte value is less than the scalar field modulus
+pub fn is_canonical_scalar(value: &[u8; 32]) -> bool {
+    value.as_slice() < SCALAR_FIELD_MODULUS.as_slice()
+}
+
+/// Read a gnark G1 point, mapping the infinity flag to the all-zero encoding
+fn parse_g1(bytes: &[u8]) -> Result<[u8; G1_SIZE]> {
+    let mut point: [u8; G1_SIZE] = bytes.try_into().map_err(|_| PsephosError::InvalidProof)?;
+    match point[0] & GNARK_FLAG_MASK {
+        0 => Ok(point),
+        GNARK_UNCOMPRESSED_INFINITY => {
+            point = [0u8; G1_SIZE];
+            Ok(point)
+        }
+        // Compressed encodings are not accepted
+        _ => err!(PsephosError::InvalidProof),
+    }
+}
+
+/// Read a gnark G2 point, mapping the infinity flag to the all-zero encoding
+fn parse_g2(bytes: &[u8]) -> Result<[u8; G2_SIZE]> {
+    let mut point: [u8; G2_SIZE] = bytes.try_into().map_err(|_| PsephosError::InvalidProof)?;
+    match point[0] & GNARK_FLAG_MASK {
+        0 => Ok(point),
+        GNARK_UNCOMPRESSED_INFINITY => {
+            point = [0u8; G2_SIZE];
+            Ok(point)
+        }
+        _ => err!(PsephosError::InvalidProof),
+    }
+}
+
+/// Negate a G1 point: (x, y) -> (x, q - y)
+pub(crate) fn negate_g1(point: &[u8; G1_SIZE]) -> [u8; G1_SIZE] {
+    if point.iter().all(|byte| *byte == 0) {
+        return *point;
+    }
+
+    let mut negated = *point;
+    let mut borrow = 0i16;
+    for i in (0..32).rev() {
+        let diff = BASE_FIELD_MODULUS[i] as i16 - point[32 + i] as i16 - borrow;
+        borrow = (diff < 0) as i16;
+        negated[32 + i] = diff.rem_euclid(256) as u8;
+    }
+    negated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CommitmentKey;
+    use ark_bn254::{Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+    use ark_ec::{CurveGroup, Group};
+    use ark_ff::{BigInteger, Field, PrimeField};
+
+    fn g1(scalar: Fr) -> G1Affine {
+        (G1Projective::generator() * scalar).into_affine()
+    }
+
+    fn g2(scalar: Fr) -> G2Affine {
+        (G2Projective::generator() * scalar).into_affine()
+    }
+
+    fn g1_bytes(point: G1Affine) -> [u8; G1_SIZE] {
+        [point.x.into_bigint().to_bytes_be(), point.y.into_bigint().to_bytes_be()]
+            .concat()
+            .try_into()
+            .unwrap()
+    }
+
+    fn g2_bytes(point: G2Affine) -> [u8; G2_SIZE] {
+        [point.x.c1, point.x.c0, point.y.c1, point.y.c0]
+            .iter()
+            .flat_map(|coordinate| coordinate.into_bigint().to_bytes_be())
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap()
+    }
+
+    fn scalar_bytes(scalar: Fr) -> [u8; 32] {
+        scalar.into_bigint().to_bytes_be().try_into().unwrap()
+    }
+
+    /// A verifying key from known trapdoor scalars and a proof for
+    /// `inputs` that satisfies its pairing equation. With `commit`, the key
+    /// takes a BSB22 commitment over the first input.
+    fn setup(inputs: &[Fr], commit: bool) -> (VerifyingKey, Groth16Proof) {
+        let (alpha, beta, gamma, delta) = (Fr::from(3u64), Fr::from(5u64), Fr::from(7u64), Fr::from(11u64));
+        let ks: Vec<Fr> = (0..inputs.len() + 1 + commit as usize).map(|i| Fr::from(13 + i as u64)).collect();
+
+        // Exponent of vk_x: k_0 + sum(input_i * k_i) [+ hash * k_last + v]
+        let mut x = ks[0] + inputs.iter().zip(&ks[1..]).map(|(input, k)| *input * k).sum::<Fr>();
+        let (commitment_key, commitment) = if commit {
+            let (v, sigma) = (Fr::from(17u64), Fr::from(19u64));
+            let point = g1_bytes(g1(v));
+            let key = CommitmentKey {
+                g: g2_bytes(g2(Fr::from(1u64))),
+                g_root_sigma_neg: g2_bytes(g2(-sigma.inverse().unwrap())),
+                committed_inputs: vec![0],
+            };
+            let prehash = [point.as_slice(), &scalar_bytes(inputs[0])].concat();
+            let hash = Fr::from_be_bytes_mod_order(&hash_to_field(&prehash, COMMITMENT_DST));
+            x += hash * ks[ks.len() - 1] + v;
+            (Some(key), Some(ProofCommitment { point, pok: g1_bytes(g1(sigma * v)) }))
+        } else {
+            (None, None)
+        };
+
+        let vk = VerifyingKey {
+            key_id: 1,
+            alpha_g1: g1_bytes(g1(alpha)),
+            beta_g2: g2_bytes(g2(beta)),
+            gamma_g2: g2_bytes(g2(gamma)),
+            delta_g2: g2_bytes(g2(delta)),
+            ic: ks.iter().map(|k| g1_bytes(g1(*k))).collect(),
+            commitment_key,
+            bump: 255,
+        };
+
+        // a * b = alpha * beta + x * gamma + c * delta
+        let (a, b) = (Fr::from(23u64), Fr::from(29u64));
+        let c = (a * b - alpha * beta - x * gamma) * delta.inverse().unwrap();
+        let proof = Groth16Proof { a: g1_bytes(g1(a)), b: g2_bytes(g2(b)), c: g1_bytes(g1(c)), commitment };
+        (vk, proof)
+    }
+
+    fn gnark_bytes(proof: &Groth16Proof) -> Vec<u8> {
+        let mut bytes = [proof.a.as_slice(), &proof.b, &proof.c].concat();
+        if let Some(commitment) = &proof.commitment {
+            bytes.extend_from_slice(&1u32.to_be_bytes());
+            bytes.extend_from_slice(&commitment.point);
+            bytes.extend_from_slice(&commitment.pok);
+        }
+        bytes
+    }
+
+    #[test]
+    fn negates_g1_points() {
+        let point = g1(Fr::from(42u64));
+        assert_eq!(negate_g1(&g1_bytes(point)), g1_bytes(-point));
+        assert_eq!(negate_g1(&[0u8; G1_SIZE]), [0u8; G1_SIZE]);
+    }
+
+    #[test]
+    fn parses_gnark_proof_lengths() {
+        let inputs = [Fr::from(1u64)];
+        let (_, proof) = setup(&inputs, true);
+        let full = gnark_bytes(&proof);
+        assert_eq!(full.len(), GNARK_PROOF_ONE_COMMITMENT_SIZE);
+
+        let parsed = Groth16Proof::from_gnark(&full).unwrap();
+        assert_eq!(parsed.c, proof.c);
+        assert_eq!(parsed.commitment.unwrap().pok, proof.commitment.unwrap().pok);
+
+        // Points only, or an empty commitment list with its PoK
+        let points = &full[..GNARK_PROOF_POINTS_SIZE];
+        assert!(Groth16Proof::from_gnark(points).unwrap().commitment.is_none());
+        let empty = [points, &0u32.to_be_bytes(), &[0u8; G1_SIZE]].concat();
+        assert!(Groth16Proof::from_gnark(&empty).unwrap().commitment.is_none());
+
+        let mut two = full.clone();
+        two[GNARK_PROOF_POINTS_SIZE + 3] = 2;
+        assert!(Groth16Proof::from_gnark(&two).is_err());
+        assert!(Groth16Proof::from_gnark(&full[..full.len() - 1]).is_err());
+        assert!(Groth16Proof::from_gnark(&empty[..GNARK_PROOF_POINTS_SIZE + 4]).is_err());
+        assert!(Groth16Proof::from_gnark(&full[..100]).is_err());
+    }
+
+    #[test]
+    fn parses_point_flags() {
+        let mut infinity = [0u8; G1_SIZE];
+        infinity[0] = GNARK_UNCOMPRESSED_INFINITY;
+        assert_eq!(parse_g1(&infinity).unwrap(), [0u8; G1_SIZE]);
+
+        let mut infinity = [0u8; G2_SIZE];
+        infinity[0] = GNARK_UNCOMPRESSED_INFINITY;
+        assert_eq!(parse_g2(&infinity).unwrap(), [0u8; G2_SIZE]);
+
+        // Compressed encodings set the top bit
+        let mut compressed = g1_bytes(g1(Fr::from(2u64)));
+        compressed[0] |= 0b1000_0000;
+        assert!(parse_g1(&compressed).is_err());
+        let mut compressed = g2_bytes(g2(Fr::from(2u64)));
+        compressed[0] |= 0b1100_0000;
+        assert!(parse_g2(&compressed).is_err());
+    }
+
+    #[test]
+    fn hashes_to_field_like_gnark() {
+        // RFC 9380 expand_message_xmd SHA-256 test vectors
+        let dst = b"QUUX-V01-CS02-with-expander-SHA256-128";
+        assert_eq!(
+            expand_message_xmd(b"", dst, 32),
+            hex_bytes("68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235")
+        );
+        assert_eq!(
+            expand_message_xmd(b"abc", dst, 32),
+            hex_bytes("d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615")
+        );
+
+        let uniform = expand_message_xmd(b"abc", COMMITMENT_DST, HASH_TO_FIELD_BYTES);
+        let expected = Fr::from_be_bytes_mod_order(&uniform);
+        assert_eq!(hash_to_field(b"abc", COMMITMENT_DST), scalar_bytes(expected));
+        assert_eq!(reduce_scalar(&SCALAR_FIELD_MODULUS), [0u8; 32]);
+    }
+
+    fn hex_bytes(hex: &str) -> Vec<u8> {
+        (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect()
+    }
+
+    #[test]
+    fn verifies_a_valid_proof() {
+        let inputs = [Fr::from(50u64), Fr::from(1u64)];
+        let public: Vec<[u8; 32]> = inputs.iter().map(|input| scalar_bytes(*input)).collect();
+        let (vk, proof) = setup(&inputs, false);
+        assert!(verify(&vk, &proof, &public).is_ok());
+
+        let tampered = [public[0], scalar_bytes(Fr::from(2u64))];
+        assert!(verify(&vk, &proof, &tampered).is_err());
+        assert!(verify(&vk, &proof, &public[..1]).is_err());
+        assert!(verify(&vk, &proof, &[public[0], SCALAR_FIELD_MODULUS]).is_err());
+    }
+
+    #[test]
+    fn verifies_a_proof_with_a_commitment() {
+        let inputs = [Fr::from(50u64), Fr::from(1u64)];
+        let public: Vec<[u8; 32]> = inputs.iter().map(|input| scalar_bytes(*input)).collect();
+        let (vk, proof) = setup(&inputs, true);
+        let proof = Groth16Proof::from_gnark(&gnark_bytes(&proof)).unwrap();
+        assert!(verify(&vk, &proof, &public).is_ok());
+
+        // The committed input feeds the commitment hash
+        let tampered = [scalar_bytes(Fr::from(51u64)), public[1]];
+        assert!(verify(&vk, &proof, &tampered).is_err());
+
+        // A proof of knowledge for another commitment fails
+        let mut bad_pok = Groth16Proof::from_gnark(&gnark_bytes(&proof)).unwrap();
+        bad_pok.commitment.as_mut().unwrap().pok = g1_bytes(g1(Fr::from(1u64)));
+        assert!(verify(&vk, &bad_pok, &public).is_err());
+
+        // Commitments must match the key
+        let (plain_vk, _) = setup(&inputs, false);
+        assert!(verify(&plain_vk, &proof, &public).is_err());
+    }
+}
@@ -1,7 +1,22 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::poseidon::{hashv, Endianness, Parameters};
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
 #[cfg(not(feature = "skip-zk-verify"))]
-use anchor_lang::solana_program::{instruction::Instruction, program::invoke};
-use anchor_spl::token_interface::{TokenInterface, TokenAccount};
+use anchor_lang::solana_program::program::invoke;
+use anchor_spl::token_interface::{self, Mint, TokenInterface, TokenAccount, TransferChecked};
+
+pub mod elgamal;
+pub mod groth16;
+pub mod ranked;
+pub mod tally;
+pub mod witness;
+
+use elgamal::Ciphertext;
+use tally::{ApprovalThreshold, ProposalOutcome, Quorum, VoteWeighting, VotingMethod};
+use witness::PublicWitness;
 
 declare_id!("DkCDEbhWqNUFto7AZQxvu2H5eiKV3whWEZDcPMqQeB4u");
 
@@ -21,62 +36,523 @@ pub const COMMITMENT_SIZE: usize = 32;
 pub const GNARK_PROOF_SIZE: usize = 388;
 /// Minimum valid proof size
 pub const MIN_PROOF_SIZE: usize = 256;
-/// Number of public inputs in our circuit (threshold, proposal_id, commitment, nullifier)
+/// Number of public inputs in our circuit (threshold, proposal_domain, commitment, nullifier)
 pub const NUM_PUBLIC_INPUTS: usize = 4;
 /// Size of each field element in the public witness (32 bytes for BN254)
 pub const FIELD_ELEMENT_SIZE: usize = 32;
-/// Public witness header size for gnark format (count + padding + elem_count = 12 bytes)
+/// Public witness header size for gnark format (nb_public + nb_secret + vector length = 12 bytes)
 pub const PUBLIC_WITNESS_HEADER_SIZE: usize = 12;
 
-/// Sunspot ZK Verifier Program ID (circuit-specific verifier for psephos_circuits)
-/// Keypair: circuits/target/psephos_circuits-keypair.json
-pub const ZK_VERIFIER_PROGRAM_ID: Pubkey = pubkey!("G616ZLAnrgeb7FrAvavozAyKmgzsuncz1XTvBYiUzh4H");
+/// Number of public inputs in the snapshot circuit (voting circuit inputs + eligibility_root)
+pub const NUM_SNAPSHOT_PUBLIC_INPUTS: usize = 5;
+/// Number of public inputs in the weighted snapshot circuit (snapshot inputs +
+/// weight_unit, weight_cap, weight)
+pub const NUM_WEIGHTED_SNAPSHOT_PUBLIC_INPUTS: usize = 8;
+/// Number of public inputs in the approval circuit (voting circuit inputs + num_options)
+pub const NUM_APPROVAL_PUBLIC_INPUTS: usize = 5;
+/// Number of public inputs in the ranked circuit (voting circuit inputs + num_options)
+pub const NUM_RANKED_PUBLIC_INPUTS: usize = 5;
+/// Depth of the token-holder snapshot Merkle tree proven in the snapshot circuit
+///
+/// A proposal's `eligibility_root` commits to a Poseidon Merkle tree of
+/// `poseidon(owner_commitment, balance)` leaves, where
+/// `owner_commitment = poseidon(voter_secret)`.
+pub const ELIGIBILITY_TREE_DEPTH: usize = 20;
+/// Number of public inputs in the reveal circuit (proposal_domain, vote_choice, commitment)
+pub const NUM_REVEAL_PUBLIC_INPUTS: usize = 3;
+
+/// Maximum number of verifiers held in the verifier registry
+pub const MAX_VERIFIERS: usize = 16;
+/// Maximum number of public inputs a stored verifying key supports
+pub const MAX_VK_PUBLIC_INPUTS: usize = 8;
+
+/// Maximum number of trustees sharing an encrypted proposal's election key
+pub const MAX_TRUSTEES: usize = 10;
+
+/// Shortest allowed voting period (seconds)
+pub const MIN_VOTING_PERIOD_SECONDS: i64 = 1;
+/// Longest allowed voting period (seconds)
+pub const MAX_VOTING_PERIOD_SECONDS: i64 = 365 * 24 * 60 * 60;
+
+/// Maximum length for a space name (it is used as a PDA seed)
+pub const MAX_SPACE_NAME_LENGTH: usize = 32;
+/// Maximum number of admins of a space
+pub const MAX_SPACE_ADMINS: usize = 5;
+/// Maximum number of accounts on a space's proposer allowlist
+pub const MAX_SPACE_PROPOSERS: usize = 16;
+
+/// Maximum number of transactions attached to one proposal option
+pub const MAX_TRANSACTIONS_PER_OPTION: u8 = 16;
+/// Maximum number of accounts in an attached instruction
+pub const MAX_TRANSACTION_ACCOUNTS: usize = 16;
+/// Maximum length of an attached instruction's data
+pub const MAX_TRANSACTION_DATA: usize = 256;
 
 #[program]
 pub mod psephos {
     use super::*;
 
+    /// Create a governance space
+    ///
+    /// A space groups the proposals of one DAO: it numbers them and supplies
+    /// the token mint, thresholds and default voting periods they use.
+    /// The space PDA is derived from the creator and `name`, so names cannot
+    /// be squatted across creators.
+    ///
+    /// If `proposers` is non-empty or the config sets a
+    /// `proposer_min_balance`, only allowlisted accounts or holders of that
+    /// many tokens can create proposals.
+    pub fn create_space(
+        ctx: Context<CreateSpace>,
+        name: String,
+        admins: Vec<Pubkey>,
+        proposers: Vec<Pubkey>,
+        config: SpaceConfig,
+    ) -> Result<()> {
+        require!(
+            !name.is_empty() && name.len() <= MAX_SPACE_NAME_LENGTH,
+            PsephosError::InvalidSpaceName
+        );
+        validate_space_members(&admins, &proposers)?;
+        config.validate()?;
+
+        let space = &mut ctx.accounts.governance_space;
+        space.creator = ctx.accounts.creator.key();
+        space.name = name;
+        space.admins = admins;
+        space.proposers = proposers;
+        space.config = config;
+        space.proposal_count = 0;
+        space.bump = ctx.bumps.governance_space;
+
+        emit!(SpaceCreated {
+            governance_space: space.key(),
+            creator: space.creator,
+            token_mint: config.token_mint,
+        });
+
+        msg!("Space '{}' created", space.name);
+        Ok(())
+    }
+
+    /// Replace a space's admin set, proposer allowlist and configuration
+    ///
+    /// Only an admin can call this. Existing proposals keep the settings
+    /// (and deposit) they were created with.
+    pub fn update_space(
+        ctx: Context<UpdateSpace>,
+        admins: Vec<Pubkey>,
+        proposers: Vec<Pubkey>,
+        config: SpaceConfig,
+    ) -> Result<()> {
+        validate_space_members(&admins, &proposers)?;
+        config.validate()?;
+
+        let space = &mut ctx.accounts.governance_space;
+        space.admins = admins;
+        space.proposers = proposers;
+        space.config = config;
+
+        emit!(SpaceUpdated {
+            governance_space: space.key(),
+            admin: ctx.accounts.admin.key(),
+            token_mint: config.token_mint,
+        });
+
+        msg!("Space '{}' updated", space.name);
+        Ok(())
+    }
+
+    /// Deposit SOL into a space's treasury
+    ///
+    /// The treasury is a system-owned PDA (`[b"treasury", space]`) that
+    /// only `execute_proposal` can sign for, so funds leave it only through
+    /// executed proposals. The first deposit must cover the rent-exempt
+    /// minimum of an empty account.
+    pub fn deposit_to_treasury(ctx: Context<DepositToTreasury>, amount: u64) -> Result<()> {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.depositor.to_account_info(),
+                    to: ctx.accounts.treasury.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        emit!(TreasuryDeposit {
+            governance_space: ctx.accounts.governance_space.key(),
+            depositor: ctx.accounts.depositor.key(),
+            mint: None,
+            amount,
+        });
+
+        msg!("Deposited {} lamports into the treasury", amount);
+        Ok(())
+    }
+
+    /// Create the treasury's token vault for `mint`
+    ///
+    /// The vault is a token account PDA (`[b"treasury_vault", space, mint]`)
+    /// owned by the treasury; SPL Token and Token-2022 mints are supported.
+    /// Anyone can pay to create it.
+    pub fn init_treasury_vault(ctx: Context<InitTreasuryVault>) -> Result<()> {
+        msg!(
+            "Treasury vault for mint {} created at {}",
+            ctx.accounts.mint.key(),
+            ctx.accounts.vault.key()
+        );
+        Ok(())
+    }
+
+    /// Deposit tokens into the treasury's vault for their mint
+    pub fn deposit_tokens_to_treasury(ctx: Context<DepositTokensToTreasury>, amount: u64) -> Result<()> {
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.depositor_token_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.depositor.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        emit!(TreasuryDeposit {
+            governance_space: ctx.accounts.governance_space.key(),
+            depositor: ctx.accounts.depositor.key(),
+            mint: Some(ctx.accounts.mint.key()),
+            amount,
+        });
+
+        msg!("Deposited {} tokens into the treasury vault", amount);
+        Ok(())
+    }
+
     /// Create a new proposal for voting
+    ///
+    /// The proposal takes the next ID in `governance_space` and copies the space's
+    /// token mint, token threshold, quorum and approval threshold, so later
+    /// space updates do not affect it. Voting opens at `start_time` (now if
+    /// `None`; the proposal is a Draft until then), and omitted periods fall
+    /// back to the space defaults.
+    ///
+    /// In a gated space the creator must be on the proposer allowlist or
+    /// pass a token account holding the space's `proposer_min_balance`.
+    /// The space's `proposal_deposit` is held in a deposit PDA until the
+    /// proposal is settled.
+    ///
+    /// `zk_verifier` pins one of the verifier registry's active entries; its
+    /// public-input layout must match the proposal's eligibility and ballot
+    /// modes. `reveal_verifier` optionally pins an entry with the `Reveal`
+    /// layout for `reveal_vote_with_proof`. If `eligibility_root` is set,
+    /// eligibility is proven against a token-holder snapshot (see
+    /// `ELIGIBILITY_TREE_DEPTH`) instead of a live token account.
+    ///
+    /// Supported combinations:
+    /// - `TokenWeighted` weighting needs the snapshot, as a live token
+    ///   account could back any number of voter secrets. A space with a
+    ///   supply quorum only takes token-weighted proposals.
+    /// - `Quadratic` voting needs token-weighted snapshot votes and no
+    ///   supply quorum.
+    /// - `Approval`, `RankedChoice` and `SingleTransferableVote` need
+    ///   commit-reveal ballots and voter token accounts. STV must leave at
+    ///   least one option unelected; it fills seats rather than picking a
+    ///   winner, so it takes no attached transactions.
+    /// - `BallotMode::Encrypted` takes one-per-voter plurality votes without a
+    ///   snapshot; the creator sets up its trustees with
+    ///   `init_encrypted_tally`.
+    #[allow(clippy::too_many_arguments)]
     pub fn create_proposal(
         ctx: Context<CreateProposal>,
-        proposal_id: u64,
         title: String,
         options: Vec<String>,
-        token_mint: Pubkey,
-        min_threshold: u64,
-        voting_period_seconds: i64,
+        start_time: Option<i64>,
+        voting_period_seconds: Option<i64>,
+        reveal_period_seconds: Option<i64>,
+        eligibility_root: Option<[u8; 32]>,
+        zk_verifier: Pubkey,
+        reveal_verifier: Option<Pubkey>,
+        ballot_mode: BallotMode,
+        weighting: VoteWeighting,
+        voting_method: VotingMethod,
     ) -> Result<()> {
-        require!(title.len() <= MAX_TITLE_LENGTH, PsephosError::TitleTooLong);
-        require!(options.len() >= 2, PsephosError::TooFewOptions);
-        require!(options.len() <= MAX_OPTIONS, PsephosError::TooManyOptions);
-        
-        for option in &options {
-            require!(option.len() <= MAX_OPTION_LENGTH, PsephosError::OptionTooLong);
+        validate_title_and_options(&title, &options)?;
+        require!(weighting.is_valid(), PsephosError::InvalidVoteWeighting);
+
+        let creator = ctx.accounts.creator.key();
+        let space = &ctx.accounts.governance_space;
+        let balance = ctx.accounts.creator_token_account.as_ref().map(|account| account.amount);
+        require!(space.can_propose(&creator, balance), PsephosError::ProposerNotEligible);
+        space.config.validate_weighting(weighting)?;
+
+        let weighted = weighting != VoteWeighting::OnePerVoter;
+        let method_supported = match voting_method {
+            VotingMethod::Plurality => true,
+            // The root is taken of the snapshot weight the circuit proves
+            VotingMethod::Quadratic => {
+                weighted
+                    && eligibility_root.is_some()
+                    && !matches!(space.config.quorum, Some(Quorum::SupplyBps(_)))
+            }
+            VotingMethod::Approval
+            | VotingMethod::RankedChoice
+            | VotingMethod::SingleTransferableVote { .. } => {
+                ballot_mode == BallotMode::CommitReveal && eligibility_root.is_none()
+            }
+        } && voting_method.is_valid(options.len());
+        require!(method_supported, PsephosError::InvalidVotingMethod);
+
+        // Pin a registered verifier whose circuit matches the eligibility mode
+        let entry = ctx.accounts.verifier_registry
+            .find(&zk_verifier)
+            .ok_or(PsephosError::VerifierNotRegistered)?;
+        require!(entry.is_active, PsephosError::VerifierNotRegistered);
+        let expected_layout = match (ballot_mode, eligibility_root.is_some()) {
+            (BallotMode::CommitReveal, false) if weighted => {
+                return err!(PsephosError::WeightingNeedsSnapshot)
+            }
+            (BallotMode::CommitReveal, false) if voting_method == VotingMethod::Approval => {
+                PublicInputLayout::ApprovalEligibility
+            }
+            (BallotMode::CommitReveal, false) if voting_method.is_ranked() => {
+                PublicInputLayout::RankedEligibility
+            }
+            (BallotMode::CommitReveal, false) => PublicInputLayout::Eligibility,
+            (BallotMode::CommitReveal, true) if voting_method == VotingMethod::Quadratic => {
+                PublicInputLayout::QuadraticSnapshot
+            }
+            (BallotMode::CommitReveal, true) if weighted => PublicInputLayout::WeightedSnapshot,
+            (BallotMode::CommitReveal, true) => PublicInputLayout::Snapshot,
+            (BallotMode::Encrypted, _) if weighted => return err!(PsephosError::WeightingUnsupported),
+            (BallotMode::Encrypted, false) => PublicInputLayout::EncryptedBallot,
+            (BallotMode::Encrypted, true) => return err!(PsephosError::EncryptedSnapshotUnsupported),
+        };
+        require!(entry.layout == expected_layout, PsephosError::VerifierLayoutMismatch);
+        let circuit_version = entry.circuit_version;
+
+        // Proof-backed reveals use the reveal circuit's verifier, if pinned
+        if let Some(reveal_verifier) = reveal_verifier {
+            let entry = ctx.accounts.verifier_registry
+                .find(&reveal_verifier)
+                .ok_or(PsephosError::VerifierNotRegistered)?;
+            require!(entry.is_active, PsephosError::VerifierNotRegistered);
+            require!(entry.layout == PublicInputLayout::Reveal, PsephosError::VerifierLayoutMismatch);
         }
 
+        let space = &mut ctx.accounts.governance_space;
+        let config = space.config;
+        let proposal_id = space.next_proposal_id();
+        space.proposal_count = proposal_id;
+
         let clock = Clock::get()?;
+        let (start_time, end_time, reveal_end_time) = voting_schedule(
+            clock.unix_timestamp,
+            start_time,
+            voting_period_seconds.unwrap_or(config.voting_period_seconds),
+            reveal_period_seconds.unwrap_or(config.reveal_period_seconds),
+        )?;
         let proposal = &mut ctx.accounts.proposal;
         
+        proposal.governance_space = space.key();
         proposal.id = proposal_id;
         proposal.creator = ctx.accounts.creator.key();
         proposal.title = title;
         proposal.options = options.clone();
-        proposal.token_mint = token_mint;
-        proposal.min_threshold = min_threshold;
-        proposal.start_time = clock.unix_timestamp;
-        proposal.end_time = clock.unix_timestamp + voting_period_seconds;
+        proposal.token_mint = config.token_mint;
+        proposal.min_threshold = config.min_threshold;
+        proposal.eligibility_root = eligibility_root;
+        proposal.zk_verifier = zk_verifier;
+        proposal.reveal_verifier = reveal_verifier;
+        proposal.ballot_mode = ballot_mode;
+        proposal.weighting = weighting;
+        proposal.voting_method = voting_method;
+        proposal.quorum = config.quorum;
+        proposal.approval_threshold = config.approval_threshold;
+        proposal.start_time = start_time;
+        proposal.end_time = end_time;
+        proposal.reveal_end_time = reveal_end_time;
         proposal.vote_count = 0;
-        proposal.is_finalized = false;
+        proposal.total_weight = 0;
+        proposal.revealed_count = 0;
+        proposal.finalize_bounty = 0;
+        proposal.execution_delay_seconds = config.execution_delay_seconds;
+        proposal.executable_at = None;
+        proposal.transaction_counts = vec![0u8; options.len()];
+        proposal.executed_count = 0;
+        proposal.status = if start_time > clock.unix_timestamp {
+            ProposalStatus::Draft
+        } else {
+            ProposalStatus::Active
+        };
         proposal.bump = ctx.bumps.proposal;
 
         // Initialize results account
         let results = &mut ctx.accounts.results;
         results.proposal = proposal.key();
         results.tallies = vec![0u64; options.len()];
-        results.is_finalized = false;
+        results.counted_weight = 0;
+        results.outcome = None;
+        results.winning_option = None;
+        results.eliminated = 0;
+        results.elected = Vec::new();
+        results.tally_cursor = 0;
+        results.round_tallies = vec![0u128; options.len()];
+        results.round_exhausted = 0;
+        results.quota = 0;
+        results.transfer = None;
+        results.rounds = Vec::new();
+        results.tally_complete = false;
         results.bump = ctx.bumps.results;
 
+        let deposit_amount = config.proposal_deposit;
+        if deposit_amount > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.creator.to_account_info(),
+                        to: ctx.accounts.deposit.to_account_info(),
+                    },
+                ),
+                deposit_amount,
+            )?;
+        }
+        let deposit = &mut ctx.accounts.deposit;
+        deposit.proposal = proposal.key();
+        deposit.amount = deposit_amount;
+        deposit.bump = ctx.bumps.deposit;
+
+        emit!(ProposalCreated {
+            proposal: proposal.key(),
+            governance_space: proposal.governance_space,
+            proposal_id,
+            creator: proposal.creator,
+            token_mint: proposal.token_mint,
+            zk_verifier,
+            reveal_verifier,
+            ballot_mode,
+            start_time: proposal.start_time,
+            end_time: proposal.end_time,
+            reveal_end_time: proposal.reveal_end_time,
+            num_options: options.len() as u8,
+        });
+
         msg!("Proposal '{}' created with {} options", proposal.title, proposal.options.len());
+        msg!("Pinned verifier {} (circuit v{})", zk_verifier, circuit_version);
+        Ok(())
+    }
+
+    /// Create the verifier registry
+    ///
+    /// Only the program's upgrade authority can create it; it becomes the
+    /// registry admin.
+    pub fn init_verifier_registry(ctx: Context<InitVerifierRegistry>) -> Result<()> {
+        let registry = &mut ctx.accounts.verifier_registry;
+        registry.admin = ctx.accounts.admin.key();
+        registry.verifiers = Vec::new();
+        registry.bump = ctx.bumps.verifier_registry;
+
+        msg!("Verifier registry created with admin {}", registry.admin);
+        Ok(())
+    }
+
+    /// Register a verifier for a circuit version
+    ///
+    /// `program_id` is either a Sunspot verifier program (verified by CPI) or
+    /// a psephos `VerifyingKey` account (verified natively with alt_bn128).
+    pub fn add_verifier(
+        ctx: Context<UpdateVerifierRegistry>,
+        program_id: Pubkey,
+        circuit_version: u32,
+        layout: PublicInputLayout,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.verifier_registry;
+        require!(registry.find(&program_id).is_none(), PsephosError::VerifierAlreadyRegistered);
+        require!(registry.verifiers.len() < MAX_VERIFIERS, PsephosError::VerifierRegistryFull);
+
+        registry.verifiers.push(VerifierEntry {
+            program_id,
+            circuit_version,
+            layout,
+            is_active: true,
+        });
+
+        emit!(VerifierAdded {
+            program_id,
+            circuit_version,
+            layout,
+        });
+
+        msg!("Verifier {} registered for circuit v{}", program_id, circuit_version);
+        Ok(())
+    }
+
+    /// Store a Groth16 verifying key for native in-program verification
+    ///
+    /// Points use the EIP-197 big-endian encoding expected by the alt_bn128
+    /// syscalls (gnark's uncompressed layout). `ic` holds one G1 point per
+    /// public input plus the constant term. Circuits with a BSB22
+    /// commitment, which includes every Sunspot-compiled Noir circuit, pass
+    /// its `commitment_key` and one more `ic` point for the commitment hash.
+    /// Register the resulting account with `add_verifier` so proposals can
+    /// pin it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn init_verifying_key(
+        ctx: Context<InitVerifyingKey>,
+        key_id: u64,
+        alpha_g1: [u8; 64],
+        beta_g2: [u8; 128],
+        gamma_g2: [u8; 128],
+        delta_g2: [u8; 128],
+        ic: Vec<[u8; 64]>,
+        commitment_key: Option<CommitmentKey>,
+    ) -> Result<()> {
+        let commitments = commitment_key.is_some() as usize;
+        require!(
+            ic.len() > commitments && ic.len() <= MAX_VK_PUBLIC_INPUTS + 1 + commitments,
+            PsephosError::InvalidVerifyingKey
+        );
+        let num_public_inputs = ic.len() - 1 - commitments;
+        if let Some(key) = &commitment_key {
+            require!(
+                key.committed_inputs.iter().all(|index| (*index as usize) < num_public_inputs),
+                PsephosError::InvalidVerifyingKey
+            );
+        }
+
+        let verifying_key = &mut ctx.accounts.verifying_key;
+        verifying_key.key_id = key_id;
+        verifying_key.alpha_g1 = alpha_g1;
+        verifying_key.beta_g2 = beta_g2;
+        verifying_key.gamma_g2 = gamma_g2;
+        verifying_key.delta_g2 = delta_g2;
+        verifying_key.ic = ic;
+        verifying_key.commitment_key = commitment_key;
+        verifying_key.bump = ctx.bumps.verifying_key;
+
+        msg!("Verifying key {} stored with {} public inputs", key_id, num_public_inputs);
+        Ok(())
+    }
+
+    /// Stop new proposals from pinning a verifier
+    ///
+    /// Proposals that already pinned it keep using it until they finish.
+    pub fn deactivate_verifier(ctx: Context<UpdateVerifierRegistry>, program_id: Pubkey) -> Result<()> {
+        let registry = &mut ctx.accounts.verifier_registry;
+        let entry = registry.verifiers
+            .iter_mut()
+            .find(|entry| entry.program_id == program_id)
+            .ok_or(PsephosError::VerifierNotRegistered)?;
+        entry.is_active = false;
+
+        emit!(VerifierDeactivated { program_id });
+
+        msg!("Verifier {} deactivated", program_id);
         Ok(())
     }
 
@@ -85,407 +561,3274 @@ pub mod psephos {
     /// The Noir circuit proof verifies:
     /// 1. Voter holds >= min_threshold tokens (private input)
     /// 2. Vote choice is valid (0-9) (private input)
-    /// 3. Nullifier = pedersen(voter_secret, proposal_id) - prevents double voting
-    /// 4. Vote commitment = pedersen(choice, secret, proposal_id) - hides the vote
+    /// 3. Nullifier = poseidon(voter_secret, proposal_domain) - prevents double voting
+    /// 4. Vote commitment = poseidon(choice, secret, proposal_domain) - hides the vote
+    ///
+    /// `proposal_domain` is the proposal's address reduced into the field
+    /// (see `proposal_domain`), so proofs and nullifiers are bound to this
+    /// proposal rather than to an ID that every space reuses.
     ///
     /// On-chain verification:
-    /// - Token balance is verified via SPL token account (voter_token_account)
-    /// - ZK proof is cryptographically verified via CPI to Sunspot verifier
+    /// - Token balance is verified via SPL token account (voter_token_account),
+    ///   or, for snapshot proposals, proven inside the circuit by Merkle
+    ///   membership in `eligibility_root` (no token account is passed)
+    /// - ZK proof is cryptographically verified via CPI to Sunspot verifier,
+    ///   or natively with alt_bn128 syscalls if the pinned verifier is a
+    ///   `VerifyingKey` account
     /// - Public witness consistency is validated against submitted values
     ///
-    /// Proof format: Gnark Groth16 proof (388 bytes) + public witness (140 bytes)
+    /// Proof format: Gnark Groth16 proof (388 bytes) + public witness
+    /// (140 bytes, 172 bytes for snapshot proposals, or 268 bytes for
+    /// token-weighted snapshot proposals).
     /// Generated using Sunspot CLI from the Noir circuit.
     pub fn cast_vote(
         ctx: Context<CastVote>,
         nullifier: [u8; 32],
         vote_commitment: [u8; 32],
         proof: Vec<u8>,           // Gnark Groth16 proof bytes (388 bytes)
-        public_witness: Vec<u8>,  // Public witness containing threshold, proposal_id, commitment, nullifier
+        public_witness: Vec<u8>,  // Public witness containing threshold, proposal_domain, commitment, nullifier
     ) -> Result<()> {
         let clock = Clock::get()?;
         let proposal = &mut ctx.accounts.proposal;
 
         // Check voting period
-        require!(clock.unix_timestamp >= proposal.start_time, PsephosError::VotingNotStarted);
-        require!(clock.unix_timestamp <= proposal.end_time, PsephosError::VotingEnded);
-        require!(!proposal.is_finalized, PsephosError::ProposalFinalized);
+        proposal.transition(ProposalStatus::Active, clock.unix_timestamp)?;
+        require!(proposal.ballot_mode == BallotMode::CommitReveal, PsephosError::BallotModeMismatch);
 
         // =========================================================================
         // TOKEN BALANCE VERIFICATION
         // =========================================================================
 
-        // Verify voter has sufficient token balance on-chain
-        require!(
-            ctx.accounts.voter_token_account.amount >= proposal.min_threshold,
-            PsephosError::InsufficientTokens
-        );
+        let mut balance = 0;
+        if proposal.eligibility_root.is_none() {
+            // Verify voter has sufficient token balance on-chain
+            let voter_token_account = ctx.accounts.voter_token_account
+                .as_ref()
+                .ok_or(PsephosError::MissingTokenAccount)?;
+            require!(
+                voter_token_account.amount >= proposal.min_threshold,
+                PsephosError::InsufficientTokens
+            );
+            balance = voter_token_account.amount;
 
-        msg!("Token balance verified: {} >= {} threshold",
-            ctx.accounts.voter_token_account.amount, proposal.min_threshold);
+            msg!("Token balance verified: {} >= {} threshold",
+                voter_token_account.amount, proposal.min_threshold);
+        } else {
+            // Balance is proven inside the circuit against the snapshot root
+            msg!("Token balance proven against eligibility snapshot");
+        }
 
         // =========================================================================
         // PROOF VALIDATION
         // =========================================================================
-        
-        // 1. Validate proof size (Gnark Groth16 proofs are 324-388 bytes)
-        require!(proof.len() >= MIN_PROOF_SIZE, PsephosError::InvalidProof);
-        require!(proof.len() <= GNARK_PROOF_SIZE + 64, PsephosError::InvalidProof);
-        
-        // 2. Validate public witness size
-        // Format: 4 bytes (count) + 4 bytes (element size) + N * 32 bytes (public inputs)
-        let expected_witness_size = PUBLIC_WITNESS_HEADER_SIZE + (NUM_PUBLIC_INPUTS * FIELD_ELEMENT_SIZE);
-        require!(public_witness.len() >= expected_witness_size, PsephosError::InvalidPublicWitness);
-        
-        // 3. Parse and validate public witness header
-        if public_witness.len() >= 8 {
-            let input_count = u32::from_be_bytes([
-                public_witness[0], public_witness[1], 
-                public_witness[2], public_witness[3]
-            ]);
-            require!(input_count == NUM_PUBLIC_INPUTS as u32, PsephosError::InvalidPublicWitness);
-        }
-        
-        // 4. Extract public inputs from witness and verify consistency
-        // Public inputs order: [min_threshold, proposal_id, vote_commitment, nullifier]
-        if public_witness.len() >= expected_witness_size {
-            // Extract min_threshold from witness (bytes 8-39, but threshold is a u64 so only last 8 bytes matter)
-            let threshold_start = PUBLIC_WITNESS_HEADER_SIZE;
-            let threshold_end = threshold_start + FIELD_ELEMENT_SIZE;
-            let witness_threshold_bytes = &public_witness[threshold_end - 8..threshold_end];
-            let witness_threshold = u64::from_be_bytes(witness_threshold_bytes.try_into().unwrap());
-            require!(witness_threshold == proposal.min_threshold, PsephosError::ThresholdMismatch);
-            
-            // Extract proposal_id from witness (bytes 40-71)
-            let proposal_start = threshold_end;
-            let proposal_end = proposal_start + FIELD_ELEMENT_SIZE;
-            let witness_proposal_bytes = &public_witness[proposal_end - 8..proposal_end];
-            let witness_proposal_id = u64::from_be_bytes(witness_proposal_bytes.try_into().unwrap());
-            require!(witness_proposal_id == proposal.id, PsephosError::ProposalIdMismatch);
-            
-            // Extract vote_commitment from witness (bytes 72-103)
-            let commitment_start = proposal_end;
-            let commitment_end = commitment_start + FIELD_ELEMENT_SIZE;
-            let witness_commitment: [u8; 32] = public_witness[commitment_start..commitment_end]
-                .try_into()
-                .map_err(|_| PsephosError::InvalidPublicWitness)?;
-            require!(witness_commitment == vote_commitment, PsephosError::CommitmentMismatch);
-            
-            // Extract nullifier from witness (bytes 104-135)
-            let nullifier_start = commitment_end;
-            let nullifier_end = nullifier_start + FIELD_ELEMENT_SIZE;
-            let witness_nullifier: [u8; 32] = public_witness[nullifier_start..nullifier_end]
-                .try_into()
-                .map_err(|_| PsephosError::InvalidPublicWitness)?;
-            require!(witness_nullifier == nullifier, PsephosError::NullifierMismatch);
-        }
-        
-        // Log proof validation success
-        msg!("ZK proof validated: {} bytes proof, {} bytes witness", proof.len(), public_witness.len());
-        msg!("Public inputs verified: threshold={}, proposal_id={}", proposal.min_threshold, proposal.id);
+
+        let proven_weight =
+            validate_vote_witness(proposal, &nullifier, &vote_commitment, &proof, &public_witness)?;
+        let weight = proven_weight.unwrap_or_else(|| proposal.vote_weight(balance));
 
         // =========================================================================
         // ON-CHAIN ZK PROOF VERIFICATION VIA CPI
         // =========================================================================
 
-        // When skip-zk-verify feature is enabled, skip the CPI (for testing with mock proofs)
-        #[cfg(not(feature = "skip-zk-verify"))]
-        {
-            // Call Sunspot verifier program to cryptographically verify the Groth16 proof
-            // The verifier expects: proof_bytes || public_witness_bytes
-            let verify_ix = Instruction {
-                program_id: ZK_VERIFIER_PROGRAM_ID,
-                accounts: vec![], // Verifier requires no accounts
-                data: [proof.as_slice(), public_witness.as_slice()].concat(),
-            };
-
-            invoke(&verify_ix, &[ctx.accounts.zk_verifier.to_account_info()])?;
-
-            msg!("ZK proof cryptographically verified on-chain!");
-        }
-
-        #[cfg(feature = "skip-zk-verify")]
-        {
-            msg!("ZK proof verification SKIPPED (skip-zk-verify feature enabled)");
-            // Still use the account to avoid unused variable warning
-            let _ = ctx.accounts.zk_verifier.key();
-        }
+        verify_groth16_proof(&ctx.accounts.zk_verifier, &proof, &public_witness)?;
         
         // =========================================================================
         // STORE VOTE
         // =========================================================================
         
-        let proposal_key = proposal.key();
-        let proposal_id = proposal.id;
+        record_vote(
+            proposal,
+            &mut ctx.accounts.vote_record,
+            ctx.bumps.vote_record,
+            ctx.accounts.voter.key(),
+            nullifier,
+            vote_commitment,
+            weight,
+            clock.unix_timestamp,
+        )?;
 
-        // Store the vote record
-        let vote_record = &mut ctx.accounts.vote_record;
-        vote_record.proposal = proposal_key;
-        vote_record.nullifier = nullifier;
-        vote_record.vote_commitment = vote_commitment;
-        vote_record.timestamp = clock.unix_timestamp;
-        vote_record.is_revealed = false;
-        vote_record.revealed_choice = None;
-        vote_record.bump = ctx.bumps.vote_record;
-
-        // Increment vote count
-        proposal.vote_count += 1;
-
-        msg!("Vote cast for proposal {} (vote #{})", proposal_id, proposal.vote_count);
+        msg!("Vote cast for proposal {} (vote #{})", proposal.id, proposal.vote_count);
         Ok(())
     }
 
-    /// Reveal and tally a vote after voting period ends
-    /// Voters can reveal their votes to be counted.
-    /// 
-    /// Security model:
-    /// - The vote_commitment was verified during cast_vote via ZK proof
-    /// - The ZK proof guaranteed: commitment = pedersen(choice, secret, proposal_id)
-    /// - The VoteRecord PDA is derived from the nullifier, so only someone who 
-    ///   knows the nullifier (from the original ZK proof) can locate this record
-    /// - At reveal, voters have no incentive to lie (they'd only hurt themselves)
-    /// - The nullifier uniqueness is enforced by PDA derivation (prevents double voting)
-    /// 
-    /// Note: We don't re-verify the nullifier hash because:
-    /// 1. The circuit uses Pedersen hash, which is expensive to compute on-chain
-    /// 2. The PDA derivation already proves knowledge of the nullifier
-    /// 3. The original ZK proof verified the nullifier was correctly computed
-    pub fn reveal_vote(
-        ctx: Context<RevealVote>,
-        vote_choice: u8,
-        _voter_secret: [u8; 32], // Kept for API compatibility, not used for verification
+    /// Cast a vote submitted by a relayer
+    ///
+    /// Proof-only variant of `cast_vote` for snapshot proposals: eligibility is
+    /// bound inside the proof (Merkle membership in `eligibility_root`), so the
+    /// voter never signs and their wallet never appears in the transaction. Any
+    /// fee payer can submit the vote and pays rent for the VoteRecord.
+    ///
+    /// If the proposal has a funded RelayerPool, the relayer is reimbursed
+    /// `fee_per_vote` lamports from it. A pool that cannot cover the fee
+    /// (while staying rent-exempt) pays nothing and the vote still counts.
+    pub fn cast_vote_relayed(
+        ctx: Context<CastVoteRelayed>,
+        nullifier: [u8; 32],
+        vote_commitment: [u8; 32],
+        proof: Vec<u8>,           // Gnark Groth16 proof bytes (388 bytes)
+        public_witness: Vec<u8>,  // Public witness including eligibility_root
     ) -> Result<()> {
         let clock = Clock::get()?;
-        let proposal = &ctx.accounts.proposal;
-        let options_len = proposal.options.len();
-        let end_time = proposal.end_time;
-        let is_finalized = proposal.is_finalized;
+        let proposal = &mut ctx.accounts.proposal;
 
-        // Can only reveal after voting ends
-        require!(clock.unix_timestamp > end_time, PsephosError::VotingNotEnded);
-        require!(!is_finalized, PsephosError::ProposalFinalized);
-        require!((vote_choice as usize) < options_len, PsephosError::InvalidVoteChoice);
+        // Check voting period
+        proposal.transition(ProposalStatus::Active, clock.unix_timestamp)?;
+        require!(proposal.ballot_mode == BallotMode::CommitReveal, PsephosError::BallotModeMismatch);
 
-        let vote_record = &mut ctx.accounts.vote_record;
-        require!(!vote_record.is_revealed, PsephosError::AlreadyRevealed);
+        // Without a snapshot root, eligibility is tied to a signer's token account
+        require!(proposal.eligibility_root.is_some(), PsephosError::RelayerRequiresSnapshot);
 
-        // The PDA derivation from nullifier proves the caller knows the nullifier
-        // which was verified by the ZK proof during cast_vote
-        // No need to re-hash - that would require Pedersen (expensive) and the
-        // ZK proof already verified the nullifier computation
+        let proven_weight =
+            validate_vote_witness(proposal, &nullifier, &vote_commitment, &proof, &public_witness)?;
+        verify_groth16_proof(&ctx.accounts.zk_verifier, &proof, &public_witness)?;
 
-        vote_record.is_revealed = true;
-        vote_record.revealed_choice = Some(vote_choice);
+        record_vote(
+            proposal,
+            &mut ctx.accounts.vote_record,
+            ctx.bumps.vote_record,
+            ctx.accounts.relayer.key(),
+            nullifier,
+            vote_commitment,
+            proven_weight.unwrap_or(1),
+            clock.unix_timestamp,
+        )?;
 
-        // Update tally
-        let results = &mut ctx.accounts.results;
-        results.tallies[vote_choice as usize] += 1;
+        // Reimburse the relayer from the proposal's pool, if any
+        if let Some(relayer_pool) = ctx.accounts.relayer_pool.as_mut() {
+            let fee = relayer_pool.fee_per_vote;
+            let pool_info = relayer_pool.to_account_info();
+            let rent_exempt = Rent::get()?.minimum_balance(pool_info.data_len());
+            let available = pool_info.lamports().saturating_sub(rent_exempt);
 
-        msg!("Vote revealed for option {}", vote_choice);
+            if fee > 0 && available >= fee {
+                **pool_info.try_borrow_mut_lamports()? -= fee;
+                **ctx.accounts.relayer.to_account_info().try_borrow_mut_lamports()? += fee;
+                relayer_pool.total_paid += fee;
+                emit!(RelayerPaid {
+                    proposal: proposal.key(),
+                    relayer: ctx.accounts.relayer.key(),
+                    amount: fee,
+                });
+                msg!("Relayer paid {} lamports", fee);
+            } else {
+                msg!("Relayer pool cannot cover fee, no payment made");
+            }
+        }
+
+        msg!("Relayed vote cast for proposal {} (vote #{})", proposal.id, proposal.vote_count);
         Ok(())
     }
 
-    /// Finalize the proposal and publish final results
-    pub fn finalize_proposal(ctx: Context<FinalizeProposal>) -> Result<()> {
-        let clock = Clock::get()?;
-        let proposal = &mut ctx.accounts.proposal;
+    /// Create the relayer fee pool for a proposal
+    ///
+    /// Only the proposal creator can set the per-vote fee. Anyone can top the
+    /// pool up afterwards with `fund_relayer_pool`.
+    pub fn init_relayer_pool(ctx: Context<InitRelayerPool>, fee_per_vote: u64) -> Result<()> {
+        let relayer_pool = &mut ctx.accounts.relayer_pool;
+        relayer_pool.proposal = ctx.accounts.proposal.key();
+        relayer_pool.creator = ctx.accounts.creator.key();
+        relayer_pool.fee_per_vote = fee_per_vote;
+        relayer_pool.total_paid = 0;
+        relayer_pool.bump = ctx.bumps.relayer_pool;
 
-        require!(clock.unix_timestamp > proposal.end_time, PsephosError::VotingNotEnded);
-        require!(!proposal.is_finalized, PsephosError::ProposalFinalized);
+        msg!("Relayer pool created with fee {} lamports per vote", fee_per_vote);
+        Ok(())
+    }
 
-        let proposal_id = proposal.id;
-        let vote_count = proposal.vote_count;
+    /// Deposit lamports into a proposal's relayer fee pool
+    pub fn fund_relayer_pool(ctx: Context<FundRelayerPool>, amount: u64) -> Result<()> {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.funder.to_account_info(),
+                    to: ctx.accounts.relayer_pool.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
 
-        proposal.is_finalized = true;
+        msg!("Relayer pool funded with {} lamports", amount);
+        Ok(())
+    }
 
-        let results = &mut ctx.accounts.results;
-        results.is_finalized = true;
+    /// Close a relayer fee pool once its proposal is settled
+    ///
+    /// The unspent fees and rent go back to the proposal creator who set the
+    /// pool up. Allowed once the proposal is finalized, or once its account
+    /// is gone (archived or cancelled).
+    pub fn close_relayer_pool(ctx: Context<CloseRelayerPool>) -> Result<()> {
+        let proposal_info = &ctx.accounts.proposal;
+        if proposal_info.owner == &crate::ID {
+            let proposal = Proposal::try_deserialize(&mut &proposal_info.try_borrow_data()?[..])?;
+            require!(
+                matches!(proposal.status, ProposalStatus::Finalized | ProposalStatus::Executed),
+                PsephosError::ProposalNotSettled
+            );
+        }
 
-        msg!("Proposal {} finalized with {} total votes", proposal_id, vote_count);
+        msg!("Relayer pool closed, remaining fees returned to {}", ctx.accounts.creator.key());
         Ok(())
     }
-}
 
-// ============================================================================
-// Account Structures
-// ============================================================================
+    /// Deposit lamports into a proposal as a bounty for whoever finalizes it
+    ///
+    /// The bounty is held by the proposal account on top of its rent and is
+    /// paid out by `finalize_proposal`, so a crank has a reason to finalize
+    /// after the reveal window even if the creator is gone.
+    pub fn fund_finalize_bounty(ctx: Context<FundFinalizeBounty>, amount: u64) -> Result<()> {
+        let clock = Clock::get()?;
+        let proposal = &ctx.accounts.proposal;
+        require!(
+            !matches!(
+                proposal.effective_status(clock.unix_timestamp),
+                ProposalStatus::Finalized | ProposalStatus::Cancelled | ProposalStatus::Executed
+            ),
+            PsephosError::InvalidStatusTransition
+        );
 
-#[account]
-#[derive(InitSpace)]
-pub struct Proposal {
-    /// Unique proposal ID
-    pub id: u64,
-    /// Creator of the proposal
-    pub creator: Pubkey,
-    /// Title of the proposal
-    #[max_len(MAX_TITLE_LENGTH)]
-    pub title: String,
-    /// Voting options
-    #[max_len(MAX_OPTIONS, MAX_OPTION_LENGTH)]
-    pub options: Vec<String>,
-    /// Token mint for eligibility check
-    pub token_mint: Pubkey,
-    /// Minimum token balance required to vote
-    pub min_threshold: u64,
-    /// Voting start time (Unix timestamp)
-    pub start_time: i64,
-    /// Voting end time (Unix timestamp)
-    pub end_time: i64,
-    /// Number of votes cast
-    pub vote_count: u64,
-    /// Whether the proposal has been finalized
-    pub is_finalized: bool,
-    /// PDA bump seed
-    pub bump: u8,
-}
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.funder.to_account_info(),
+                    to: ctx.accounts.proposal.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
 
-#[account]
-#[derive(InitSpace)]
-pub struct VoteRecord {
-    /// The proposal this vote is for
-    pub proposal: Pubkey,
-    /// Nullifier hash - unique per voter per proposal
-    pub nullifier: [u8; NULLIFIER_SIZE],
-    /// Commitment to the vote choice
-    pub vote_commitment: [u8; COMMITMENT_SIZE],
-    /// When the vote was cast
-    pub timestamp: i64,
-    /// Whether the vote has been revealed
-    pub is_revealed: bool,
-    /// The revealed vote choice (only set after reveal)
-    pub revealed_choice: Option<u8>,
-    /// PDA bump seed
-    pub bump: u8,
-}
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.finalize_bounty = proposal.finalize_bounty
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
 
-#[account]
-#[derive(InitSpace)]
-pub struct ProposalResults {
-    /// The proposal these results are for
-    pub proposal: Pubkey,
-    /// Vote tallies per option
-    #[max_len(MAX_OPTIONS)]
-    pub tallies: Vec<u64>,
-    /// Whether results are finalized
-    pub is_finalized: bool,
-    /// PDA bump seed
-    pub bump: u8,
-}
+        msg!("Finalize bounty for proposal {} is now {} lamports", proposal.id, proposal.finalize_bounty);
+        Ok(())
+    }
 
-// ============================================================================
-// Instruction Contexts
-// ============================================================================
+    /// Set up the trustees for an encrypted-ballot proposal
+    ///
+    /// `election_key` is the joint ElGamal key `H = x * G` and
+    /// `trustee_keys[i] = x_i * G` are the public halves of a Shamir sharing
+    /// of `x` at evaluation points `i + 1`, produced off-chain by the
+    /// trustees' key ceremony. Any `threshold` trustees can decrypt the tally.
+    pub fn init_encrypted_tally(
+        ctx: Context<InitEncryptedTally>,
+        election_key: [u8; 64],
+        trustee_keys: Vec<[u8; 64]>,
+        threshold: u8,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let proposal = &ctx.accounts.proposal;
+        require!(proposal.ballot_mode == BallotMode::Encrypted, PsephosError::BallotModeMismatch);
+        require!(
+            matches!(
+                proposal.effective_status(clock.unix_timestamp),
+                ProposalStatus::Draft | ProposalStatus::Active
+            ),
+            PsephosError::InvalidStatusTransition
+        );
+        require!(proposal.vote_count == 0, PsephosError::VotingStarted);
+        require!(
+            !trustee_keys.is_empty() && trustee_keys.len() <= MAX_TRUSTEES,
+            PsephosError::InvalidTrusteeSet
+        );
+        require!(
+            threshold >= 1 && threshold as usize <= trustee_keys.len(),
+            PsephosError::InvalidTrusteeSet
+        );
 
-#[derive(Accounts)]
-#[instruction(proposal_id: u64)]
-pub struct CreateProposal<'info> {
-    #[account(mut)]
-    pub creator: Signer<'info>,
+        require!(election_key != elgamal::G1_IDENTITY, PsephosError::InvalidTrusteeSet);
+        elgamal::validate_point(&election_key)?;
+        for trustee_key in &trustee_keys {
+            require!(*trustee_key != elgamal::G1_IDENTITY, PsephosError::InvalidTrusteeSet);
+            elgamal::validate_point(trustee_key)?;
+        }
 
-    #[account(
-        init,
-        payer = creator,
-        space = 8 + Proposal::INIT_SPACE,
-        seeds = [b"proposal", proposal_id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub proposal: Account<'info, Proposal>,
+        let encrypted_tally = &mut ctx.accounts.encrypted_tally;
+        encrypted_tally.proposal = proposal.key();
+        encrypted_tally.election_key = election_key;
+        encrypted_tally.decryption_shares = vec![None; trustee_keys.len()];
+        encrypted_tally.trustee_keys = trustee_keys;
+        encrypted_tally.threshold = threshold;
+        encrypted_tally.aggregate = Ciphertext::IDENTITY;
+        encrypted_tally.is_decrypted = false;
+        encrypted_tally.bump = ctx.bumps.encrypted_tally;
 
-    #[account(
-        init,
-        payer = creator,
-        space = 8 + ProposalResults::INIT_SPACE,
-        seeds = [b"results", proposal_id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub results: Account<'info, ProposalResults>,
+        emit!(EncryptedTallyInitialized {
+            proposal: proposal.key(),
+            election_key,
+            num_trustees: encrypted_tally.trustee_keys.len() as u8,
+            threshold,
+        });
 
-    pub system_program: Program<'info, System>,
-}
+        msg!("Encrypted tally created with {}-of-{} trustees", threshold, encrypted_tally.trustee_keys.len());
+        Ok(())
+    }
 
-#[derive(Accounts)]
-#[instruction(nullifier: [u8; 32], vote_commitment: [u8; 32], proof: Vec<u8>, public_witness: Vec<u8>)]
-pub struct CastVote<'info> {
-    #[account(mut)]
-    pub voter: Signer<'info>,
+    /// Cast an encrypted ballot with ZK proof
+    ///
+    /// The encrypted-ballot circuit (circuits/encrypted) proves the voting
+    /// circuit's eligibility and nullifier statements, and that `ciphertext`
+    /// is a well-formed encryption of `2^(24 * choice)` under the election key
+    /// with `choice < options.len()`. Its public inputs replace the vote
+    /// commitment with `ballot_hash` (see `elgamal::ballot_hash`), which is
+    /// recomputed here from the submitted ciphertext.
+    ///
+    /// The ciphertext is added into the proposal's aggregate; the choice is
+    /// never revealed.
+    pub fn cast_encrypted_vote(
+        ctx: Context<CastEncryptedVote>,
+        nullifier: [u8; 32],
+        ciphertext: Ciphertext,
+        proof: Vec<u8>,           // Gnark Groth16 proof bytes
+        public_witness: Vec<u8>,  // Public witness containing threshold, proposal_domain, ballot_hash, nullifier
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let proposal = &mut ctx.accounts.proposal;
 
-    #[account(
-        mut,
-        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        // Check voting period
+        proposal.transition(ProposalStatus::Active, clock.unix_timestamp)?;
+        require!(proposal.ballot_mode == BallotMode::Encrypted, PsephosError::BallotModeMismatch);
+
+        // A slot overflowing would corrupt the neighbouring option's count
+        require!(proposal.vote_count < elgamal::MAX_SLOT_TALLY, PsephosError::EncryptedTallyFull);
+
+        require!(
+            ctx.accounts.voter_token_account.amount >= proposal.min_threshold,
+            PsephosError::InsufficientTokens
+        );
+
+        let encrypted_tally = &mut ctx.accounts.encrypted_tally;
+        // The option count is read at vote time so amending the options
+        // before the first vote keeps ballots consistent
+        let key_hash = elgamal::key_hash(&encrypted_tally.election_key, proposal.options.len() as u8)?;
+        let ballot_hash = elgamal::ballot_hash(&key_hash, &ciphertext)?;
+
+        validate_vote_witness(proposal, &nullifier, &ballot_hash, &proof, &public_witness)?;
+        verify_groth16_proof(&ctx.accounts.zk_verifier, &proof, &public_witness)?;
+
+        // Rejects points that are not on the curve
+        encrypted_tally.aggregate = encrypted_tally.aggregate.add(&ciphertext)?;
+
+        record_vote(
+            proposal,
+            &mut ctx.accounts.vote_record,
+            ctx.bumps.vote_record,
+            ctx.accounts.voter.key(),
+            nullifier,
+            ballot_hash,
+            1,
+            clock.unix_timestamp,
+        )?;
+
+        msg!("Encrypted vote cast for proposal {} (vote #{})", proposal.id, proposal.vote_count);
+        Ok(())
+    }
+
+    /// Post a trustee's decryption share for the aggregate ballot
+    ///
+    /// `share = x_i * C1` where `C1` is the aggregate's first component. The
+    /// Chaum-Pedersen proof `(challenge, response)` shows it uses the same
+    /// secret as `trustee_keys[trustee_index]`, so anyone holding the share
+    /// can submit it.
+    pub fn submit_decryption_share(
+        ctx: Context<SubmitDecryptionShare>,
+        trustee_index: u8,
+        share: [u8; 64],
+        challenge: [u8; 32],
+        response: [u8; 32],
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let proposal = &mut ctx.accounts.proposal;
+
+        proposal.transition(ProposalStatus::Revealing, clock.unix_timestamp)?;
+
+        let encrypted_tally = &mut ctx.accounts.encrypted_tally;
+        require!(!encrypted_tally.is_decrypted, PsephosError::TallyAlreadyDecrypted);
+
+        let index = trustee_index as usize;
+        let trustee_key = *encrypted_tally.trustee_keys
+            .get(index)
+            .ok_or(PsephosError::InvalidTrusteeIndex)?;
+        require!(
+            encrypted_tally.decryption_shares[index].is_none(),
+            PsephosError::DecryptionShareAlreadySubmitted
+        );
+
+        elgamal::verify_dleq(
+            &proposal.key(),
+            &trustee_key,
+            &encrypted_tally.aggregate.c1,
+            &share,
+            &challenge,
+            &response,
+        )?;
+
+        encrypted_tally.decryption_shares[index] = Some(share);
+
+        emit!(DecryptionShareSubmitted {
+            proposal: proposal.key(),
+            trustee_index,
+            share,
+        });
+
+        msg!("Decryption share {} accepted", trustee_index);
+        Ok(())
+    }
+
+    /// Publish the decrypted tally of an encrypted-ballot proposal
+    ///
+    /// Combines the shares of exactly `threshold` trustees (`participants`)
+    /// and writes `tallies` to `ProposalResults` if they match the aggregate
+    /// ciphertext. Lagrange coefficients are applied without on-chain field
+    /// inversion: `scaled_shares[k]` must be participant `k`'s share divided
+    /// by `prod_{j != k} |x_j - x_k|` (see `elgamal::combine_shares`).
+    pub fn decrypt_tally(
+        ctx: Context<DecryptTally>,
+        tallies: Vec<u64>,
+        participants: Vec<u8>,
+        scaled_shares: Vec<[u8; 64]>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let proposal = &mut ctx.accounts.proposal;
+
+        proposal.transition(ProposalStatus::Revealing, clock.unix_timestamp)?;
+
+        let encrypted_tally = &mut ctx.accounts.encrypted_tally;
+        require!(!encrypted_tally.is_decrypted, PsephosError::TallyAlreadyDecrypted);
+        require!(
+            participants.len() == encrypted_tally.threshold as usize
+                && scaled_shares.len() == participants.len(),
+            PsephosError::InvalidDecryptionShare
+        );
+
+        require!(tallies.len() == proposal.options.len(), PsephosError::TallyDecryptionFailed);
+        let total = tallies
+            .iter()
+            .try_fold(0u64, |total, tally| total.checked_add(*tally))
+            .ok_or(PsephosError::TallyDecryptionFailed)?;
+        require!(total == proposal.vote_count, PsephosError::TallyDecryptionFailed);
+
+        let mut shares = Vec::with_capacity(participants.len());
+        for (index, scaled_share) in participants.iter().zip(scaled_shares) {
+            let share = encrypted_tally.decryption_shares
+                .get(*index as usize)
+                .ok_or(PsephosError::InvalidTrusteeIndex)?
+                .ok_or(PsephosError::MissingDecryptionShare)?;
+            shares.push(elgamal::ShareInput {
+                x: *index as u64 + 1,
+                share,
+                scaled_share,
+            });
+        }
+
+        let decryption_factor = elgamal::combine_shares(&shares)?;
+        elgamal::verify_tallies(&encrypted_tally.aggregate, &decryption_factor, &tallies)?;
+
+        encrypted_tally.is_decrypted = true;
+        ctx.accounts.results.tallies = tallies.clone();
+        ctx.accounts.results.counted_weight = total;
+
+        emit!(TallyDecrypted {
+            proposal: proposal.key(),
+            tallies,
+        });
+
+        msg!("Encrypted tally for proposal {} decrypted", proposal.id);
+        Ok(())
+    }
+
+    /// Reveal and tally a vote after voting period ends
+    /// Voters can reveal their votes to be counted until the reveal window,
+    /// `reveal_period_seconds` after voting ends, closes.
+    ///
+    /// Security model:
+    /// - The ZK proof verified during cast_vote guaranteed:
+    ///   commitment = poseidon(choice, secret, proposal_domain)
+    /// - At reveal, the commitment is recomputed on-chain with the Poseidon
+    ///   syscall from the submitted (choice, secret) and the proposal's
+    ///   domain, and
+    ///   must match the stored VoteRecord.vote_commitment
+    /// - Only the voter knows the secret, so nobody else can open the
+    ///   commitment to a different choice
+    /// - The nullifier uniqueness is enforced by PDA derivation (prevents double voting)
+    ///
+    /// For approval proposals `vote_choice` is the committed bitmask of
+    /// approved options, and the vote's weight is added to each of them.
+    pub fn reveal_vote(
+        ctx: Context<RevealVote>,
+        vote_choice: u16,
+        voter_secret: [u8; 32], // Big-endian BN254 field element
+    ) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let domain = proposal_domain(&proposal.key());
+        check_reveal(proposal, &ctx.accounts.vote_record)?;
+        check_ballot(proposal, vote_choice)?;
+
+        // Recompute the commitment and check it opens to the submitted choice
+        let commitment = compute_vote_commitment(vote_choice as u64, &voter_secret, &domain)?;
+        require!(
+            commitment == ctx.accounts.vote_record.vote_commitment,
+            PsephosError::InvalidReveal
+        );
+
+        apply_reveal(proposal, &mut ctx.accounts.vote_record, &mut ctx.accounts.results, vote_choice);
+
+        msg!("Vote revealed for option {}", vote_choice);
+        Ok(())
+    }
+
+    /// Reveal and tally a vote using a ZK proof of the commitment opening
+    ///
+    /// Alternative to `reveal_vote` that keeps the voter secret off-chain.
+    /// The reveal circuit (circuits/reveal) proves:
+    ///   vote_commitment = poseidon(vote_choice, voter_secret, proposal_domain)
+    /// with proposal_domain, vote_choice and vote_commitment as public inputs.
+    ///
+    /// The public witness is checked against the proposal, the submitted choice
+    /// and the stored VoteRecord commitment, then the Groth16 proof is verified
+    /// against the reveal verifier pinned on the proposal at creation, exactly
+    /// as `cast_vote` does for the voting circuit.
+    pub fn reveal_vote_with_proof(
+        ctx: Context<RevealVoteWithProof>,
+        vote_choice: u16,
+        proof: Vec<u8>,           // Gnark Groth16 proof bytes
+        public_witness: Vec<u8>,  // Public witness containing proposal_domain, vote_choice, commitment
+    ) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let domain = proposal_domain(&proposal.key());
+        check_reveal(proposal, &ctx.accounts.vote_record)?;
+        check_ballot(proposal, vote_choice)?;
+
+        // 1. Validate proof size
+        require!(proof.len() >= MIN_PROOF_SIZE, PsephosError::InvalidProof);
+        require!(proof.len() <= GNARK_PROOF_SIZE + 64, PsephosError::InvalidProof);
+
+        // 2. Parse the public witness strictly
+        let witness = PublicWitness::parse_expected(&public_witness, NUM_REVEAL_PUBLIC_INPUTS)?;
+
+        // 3. Verify public inputs match the proposal, choice and stored commitment
+        // Public inputs order: [proposal_domain, vote_choice, vote_commitment]
+        require!(*witness.field(0)? == domain, PsephosError::ProposalDomainMismatch);
+        require!(witness.u64_at(1)? == vote_choice as u64, PsephosError::InvalidReveal);
+        require!(
+            *witness.field(2)? == ctx.accounts.vote_record.vote_commitment,
+            PsephosError::CommitmentMismatch
+        );
+
+        // 4. Verify the opening proof via CPI to the reveal verifier
+        verify_groth16_proof(&ctx.accounts.zk_verifier, &proof, &public_witness)?;
+
+        apply_reveal(proposal, &mut ctx.accounts.vote_record, &mut ctx.accounts.results, vote_choice);
+
+        msg!("Vote revealed with proof for option {}", vote_choice);
+        Ok(())
+    }
+
+    /// Create the buffer that collects a ranked-choice proposal's revealed
+    /// ballots
+    ///
+    /// Anyone can create it; it must exist before the first ranked reveal.
+    pub fn init_ranked_ballots(ctx: Context<InitRankedBallots>) -> Result<()> {
+        let proposal = &ctx.accounts.proposal;
+        require!(proposal.voting_method.is_ranked(), PsephosError::VotingMethodMismatch);
+
+        let ranked_ballots = &mut ctx.accounts.ranked_ballots;
+        ranked_ballots.proposal = proposal.key();
+        ranked_ballots.ballot_count = 0;
+        ranked_ballots.bump = ctx.bumps.ranked_ballots;
+
+        msg!("Ranked ballot buffer created for proposal {}", proposal.id);
+        Ok(())
+    }
+
+    /// Reveal a ranked ballot into the proposal's ballot buffer
+    ///
+    /// `ranking` lists distinct options from most to least preferred and may
+    /// stop early; the vote commitment must open to its packed form (see
+    /// `ranked::pack_ranking`). The revealer pays rent for the buffer entry.
+    /// Ballots are counted once the reveal window closes by `tally_round`.
+    pub fn reveal_ranked_vote(
+        ctx: Context<RevealRankedVote>,
+        ranking: Vec<u8>,
+        voter_secret: [u8; 32], // Big-endian BN254 field element
+    ) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let domain = proposal_domain(&proposal.key());
+        check_reveal(proposal, &ctx.accounts.vote_record)?;
+        require!(proposal.voting_method.is_ranked(), PsephosError::VotingMethodMismatch);
+
+        let packed = ranked::pack_ranking(&ranking, proposal.options.len())
+            .ok_or(PsephosError::InvalidRanking)?;
+        let commitment = compute_vote_commitment(packed, &voter_secret, &domain)?;
+        let vote_record = &mut ctx.accounts.vote_record;
+        require!(commitment == vote_record.vote_commitment, PsephosError::InvalidReveal);
+
+        // The account was grown by one entry in the accounts constraints
+        let ranked_ballots = &mut ctx.accounts.ranked_ballots;
+        let weight = vote_record.weight;
+        {
+            let info = ranked_ballots.to_account_info();
+            let mut data = info.try_borrow_mut_data()?;
+            ranked::write_ballot(
+                &mut data[RankedBallots::ENTRIES_OFFSET..],
+                ranked_ballots.ballot_count as usize,
+                &ranked::RankedBallot::new(weight, packed),
+            );
+        }
+        ranked_ballots.ballot_count += 1;
+
+        proposal.revealed_count += 1;
+        vote_record.is_revealed = true;
+        vote_record.revealed_choice = Some(ranking[0] as u16);
+        ctx.accounts.results.counted_weight += weight;
+
+        emit!(RankedVoteRevealed {
+            proposal: vote_record.proposal,
+            nullifier: vote_record.nullifier,
+            ranking: ranking.clone(),
+            weight,
+        });
+
+        msg!("Ranked vote revealed with {} preferences", ranking.len());
+        Ok(())
+    }
+
+    /// Count up to `max_ballots` ranked ballots toward the current tally
+    /// round
+    ///
+    /// Permissionless crank, available once the reveal window has closed or
+    /// every vote is revealed; call it until `tally_complete` is set. Each
+    /// ballot counts for its highest-ranked option still in the race. When
+    /// a round's last ballot is counted the round is recorded in
+    /// `ProposalResults.rounds` and decided (see `ranked`):
+    ///
+    /// - `RankedChoice`: an option with a majority of the counted weight
+    ///   wins, otherwise the last-placed option is eliminated.
+    /// - `SingleTransferableVote`: the leader is elected if it reaches the
+    ///   Droop quota and its surplus is passed on during the next round,
+    ///   otherwise the last-placed option is eliminated. Elected options are
+    ///   appended to `ProposalResults.elected`.
+    ///
+    /// The next round starts from the first ballot (see
+    /// `ranked::count_slice`). `finalize_proposal` waits for the tally to
+    /// complete.
+    pub fn tally_round(ctx: Context<TallyRound>, max_ballots: u32) -> Result<()> {
+        let clock = Clock::get()?;
+        let proposal = &ctx.accounts.proposal;
+        require!(proposal.voting_method.is_ranked(), PsephosError::VotingMethodMismatch);
+        require!(
+            proposal.effective_status(clock.unix_timestamp) == ProposalStatus::Revealing,
+            PsephosError::InvalidStatusTransition
+        );
+        require!(
+            clock.unix_timestamp > proposal.reveal_end_time
+                || proposal.revealed_count == proposal.vote_count,
+            PsephosError::RevealWindowOpen
+        );
+
+        let results = &mut ctx.accounts.results;
+        require!(!results.tally_complete, PsephosError::RankedTallyComplete);
+
+        let start = results.tally_cursor;
+        let (closed, ballot_count) = match &ctx.accounts.ranked_ballots {
+            Some(ranked_ballots) => {
+                let ballot_count = ranked_ballots.ballot_count;
+                let info = ranked_ballots.to_account_info();
+                let mut data = info.try_borrow_mut_data()?;
+                let entries = &mut data[RankedBallots::ENTRIES_OFFSET..];
+                let closed = ranked::count_slice(results, proposal.voting_method, entries, ballot_count, max_ballots);
+                (closed, ballot_count)
+            }
+            // Without a buffer nothing was revealed
+            None => {
+                require!(proposal.revealed_count == 0, PsephosError::MissingRankedBallots);
+                (ranked::count_slice(results, proposal.voting_method, &mut [], 0, max_ballots), 0)
+            }
+        };
+        let Some(closed) = closed else {
+            msg!("Counted ballots {}..{} of {}", start, results.tally_cursor, ballot_count);
+            return Ok(());
+        };
+
+        let round = results.rounds.last().expect("a closed round is recorded");
+        emit!(TallyRoundCompleted {
+            proposal: proposal.key(),
+            round: results.rounds.len() as u8,
+            tallies: round.tallies.clone(),
+            exhausted_weight: round.exhausted_weight,
+            eliminated: round.eliminated,
+            elected: closed.elected,
+            complete: results.tally_complete,
+        });
+
+        msg!("Tally round {} complete: {:?}", results.rounds.len(), closed.decision);
+        Ok(())
+    }
+
+    /// Finalize the proposal and publish final results
+    ///
+    /// Once the reveal window has closed anyone can finalize as a crank.
+    /// Before that, only the creator can, and only if every vote is already
+    /// counted. Encrypted-ballot proposals must have their tally decrypted
+    /// first, which counts every vote.
+    ///
+    /// The outcome checks the counted votes against the proposal's optional
+    /// quorum and the option with the most votes against its approval
+    /// threshold. A single transferable vote proposal that meets quorum
+    /// passes if it elected any option, without an approval threshold and
+    /// without a winning option, so nothing is executed for it. The deposit
+    /// is refunded to the creator unless the proposal misses quorum, in
+    /// which case it goes to the space treasury.
+    ///
+    /// Whoever finalizes receives the proposal's finalize bounty, if funded.
+    pub fn finalize_proposal(ctx: Context<FinalizeProposal>) -> Result<()> {
+        let clock = Clock::get()?;
+        let proposal = &mut ctx.accounts.proposal;
+
+        proposal.transition(ProposalStatus::Finalized, clock.unix_timestamp)?;
+        let ranked = proposal.voting_method.is_ranked();
+        if ranked {
+            require!(ctx.accounts.results.tally_complete, PsephosError::RankedTallyIncomplete);
+        }
+        let all_counted = match proposal.ballot_mode {
+            BallotMode::CommitReveal => proposal.revealed_count == proposal.vote_count,
+            BallotMode::Encrypted => {
+                let encrypted_tally = ctx.accounts.encrypted_tally
+                    .as_ref()
+                    .ok_or(PsephosError::TallyNotDecrypted)?;
+                require!(encrypted_tally.is_decrypted, PsephosError::TallyNotDecrypted);
+                true
+            }
+        };
+        if clock.unix_timestamp <= proposal.reveal_end_time {
+            // Early path: creator only, with nothing left to reveal
+            require!(proposal.creator == ctx.accounts.authority.key(), PsephosError::Unauthorized);
+            require!(all_counted, PsephosError::RevealWindowOpen);
+        }
+
+        let bounty = proposal.finalize_bounty;
+        if bounty > 0 {
+            proposal.finalize_bounty = 0;
+            **proposal.to_account_info().try_borrow_mut_lamports()? -= bounty;
+            **ctx.accounts.authority.to_account_info().try_borrow_mut_lamports()? += bounty;
+            msg!("Finalize bounty of {} lamports paid", bounty);
+        }
+
+        let proposal_id = proposal.id;
+        let vote_count = proposal.vote_count;
+        let results = &mut ctx.accounts.results;
+
+        // Quorum counts votes that made it into the tally
+        let counted = results.counted_weight;
+        let quorum_met = match proposal.quorum {
+            None => true,
+            Some(quorum @ Quorum::Votes(_)) => quorum.is_met(counted, 0),
+            Some(quorum @ Quorum::SupplyBps(_)) => {
+                let token_mint = ctx.accounts.token_mint
+                    .as_ref()
+                    .ok_or(PsephosError::MissingTokenMint)?;
+                quorum.is_met(counted, proposal.weighting.supply_weight(token_mint.supply))
+            }
+        };
+        // Ranked tallies are the final runoff round, without exhausted ballots
+        let threshold_base = if ranked { results.tallies.iter().sum() } else { counted };
+        let stv = matches!(proposal.voting_method, VotingMethod::SingleTransferableVote { .. });
+        let (outcome, winning_option) = if stv && quorum_met {
+            // Seats are won by quota rather than the approval threshold, and
+            // no single option wins to execute transactions for
+            if results.elected.is_empty() {
+                (ProposalOutcome::Rejected, None)
+            } else {
+                (ProposalOutcome::Passed, None)
+            }
+        } else {
+            tally::compute_outcome(
+                &results.tallies,
+                threshold_base,
+                quorum_met,
+                proposal.approval_threshold,
+            )
+        };
+        results.outcome = Some(outcome);
+        results.winning_option = winning_option;
+
+        // Attached transactions of the winning option run after the timelock
+        if outcome == ProposalOutcome::Passed {
+            let executable_at = clock.unix_timestamp
+                .checked_add(proposal.execution_delay_seconds)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            proposal.executable_at = Some(executable_at);
+        }
+
+        settle_deposit(
+            &mut ctx.accounts.deposit,
+            &ctx.accounts.creator,
+            &ctx.accounts.treasury,
+            outcome == ProposalOutcome::QuorumNotMet,
+        )?;
+
+        emit!(ProposalFinalized {
+            proposal: proposal.key(),
+            proposal_id,
+            vote_count,
+            tallies: results.tallies.clone(),
+            outcome,
+            winning_option,
+            finalizer: ctx.accounts.authority.key(),
+            bounty,
+        });
+
+        msg!("Proposal {} finalized with {} total votes: {:?}", proposal_id, vote_count, outcome);
+        Ok(())
+    }
+
+    /// Withdraw a proposal, or remove it as spam
+    ///
+    /// Moves the proposal to Cancelled and closes it together with its
+    /// results, ranked ballot buffer, encrypted tally and attached
+    /// transactions, refunding rent and any finalize bounty to the creator.
+    /// Ranked and encrypted-ballot proposals must pass those accounts'
+    /// addresses even if they were never set up; every attached transaction
+    /// is passed in the remaining accounts. The creator can cancel before the
+    /// first vote and gets the deposit back; a space admin can cancel until
+    /// voting ends, which slashes the deposit to the space treasury. The
+    /// deposit PDA is closed to the creator either way.
+    pub fn cancel_proposal(ctx: Context<CancelProposal>) -> Result<()> {
+        let clock = Clock::get()?;
+        let authority = ctx.accounts.authority.key();
+        let proposal = &mut ctx.accounts.proposal;
+        let as_spam = authority != proposal.creator;
+        if !as_spam {
+            require!(proposal.vote_count == 0, PsephosError::VotingStarted);
+        }
+
+        proposal.transition(ProposalStatus::Cancelled, clock.unix_timestamp)?;
+        settle_deposit(&mut ctx.accounts.deposit, &ctx.accounts.creator, &ctx.accounts.treasury, as_spam)?;
+        if proposal.ballot_mode == BallotMode::Encrypted {
+            let encrypted_tally = ctx.accounts.encrypted_tally
+                .as_ref()
+                .ok_or(PsephosError::MissingEncryptedTally)?;
+            close_if_initialized(encrypted_tally, &ctx.accounts.creator)?;
+        }
+        if proposal.voting_method.is_ranked() {
+            let ranked_ballots = ctx.accounts.ranked_ballots
+                .as_ref()
+                .ok_or(PsephosError::MissingRankedBallots)?;
+            close_if_initialized(ranked_ballots, &ctx.accounts.creator)?;
+        }
+        close_transactions(proposal, ctx.remaining_accounts, &ctx.accounts.creator)?;
+
+        emit!(ProposalCancelled {
+            proposal: proposal.key(),
+            proposal_id: proposal.id,
+            cancelled_by: authority,
+            deposit_slashed: as_spam,
+        });
+
+        msg!("Proposal {} cancelled", proposal.id);
+        Ok(())
+    }
+
+    /// Edit a proposal's title, options and schedule before the first vote
+    ///
+    /// The new schedule is validated like `create_proposal`'s, with omitted
+    /// periods again taken from the space's defaults; the tallies are reset
+    /// to the new option count.
+    pub fn amend_proposal(
+        ctx: Context<AmendProposal>,
+        title: String,
+        options: Vec<String>,
+        start_time: Option<i64>,
+        voting_period_seconds: Option<i64>,
+        reveal_period_seconds: Option<i64>,
+    ) -> Result<()> {
+        validate_title_and_options(&title, &options)?;
+
+        let config = ctx.accounts.governance_space.config;
+        let clock = Clock::get()?;
+        let (start_time, end_time, reveal_end_time) = voting_schedule(
+            clock.unix_timestamp,
+            start_time,
+            voting_period_seconds.unwrap_or(config.voting_period_seconds),
+            reveal_period_seconds.unwrap_or(config.reveal_period_seconds),
+        )?;
+
+        let proposal = &mut ctx.accounts.proposal;
+        require!(proposal.vote_count == 0, PsephosError::VotingStarted);
+        proposal.reschedule(start_time, end_time, reveal_end_time, clock.unix_timestamp)?;
+        proposal.title = title;
+        // Transactions stay attached to the options that are kept
+        require!(
+            proposal.transaction_counts.iter().skip(options.len()).all(|count| *count == 0),
+            PsephosError::ProposalHasTransactions
+        );
+        require!(proposal.voting_method.is_valid(options.len()), PsephosError::InvalidVotingMethod);
+        proposal.transaction_counts.resize(options.len(), 0);
+        proposal.options = options;
+
+        let results = &mut ctx.accounts.results;
+        results.tallies = vec![0u64; proposal.options.len()];
+        results.round_tallies = vec![0u128; proposal.options.len()];
+        results.counted_weight = 0;
+
+        emit!(ProposalAmended {
+            proposal: proposal.key(),
+            proposal_id: proposal.id,
+            num_options: proposal.options.len() as u8,
+            start_time,
+            end_time,
+            reveal_end_time,
+        });
+
+        msg!("Proposal {} amended", proposal.id);
+        Ok(())
+    }
+
+    /// Close a vote record once its proposal is settled
+    ///
+    /// The record's `refund_key` (the account that paid its rent) signs and
+    /// chooses where the rent goes. Allowed once the proposal is finalized,
+    /// or once its account is gone (archived or cancelled).
+    pub fn close_vote_record(ctx: Context<CloseVoteRecord>) -> Result<()> {
+        let proposal_info = &ctx.accounts.proposal;
+        if proposal_info.owner == &crate::ID {
+            let proposal = Proposal::try_deserialize(&mut &proposal_info.try_borrow_data()?[..])?;
+            require!(
+                matches!(proposal.status, ProposalStatus::Finalized | ProposalStatus::Executed),
+                PsephosError::ProposalNotSettled
+            );
+        }
+
+        msg!("Vote record closed, rent returned to {}", ctx.accounts.recipient.key());
+        Ok(())
+    }
+
+    /// Replace a settled proposal with a compact archive entry
+    ///
+    /// Keeps the tallies and outcome plus a hash of the title and options in
+    /// a `ProposalArchive`, and closes the proposal, its results, deposit,
+    /// ranked ballot buffer, encrypted tally, relayer pool (if passed) and
+    /// unexecuted attached transactions, returning their rent to the
+    /// creator. Ranked and encrypted-ballot proposals must pass the buffer's
+    /// or tally's address even if it was never set up; the transactions are
+    /// passed in the remaining accounts.
+    pub fn archive_proposal(ctx: Context<ArchiveProposal>) -> Result<()> {
+        let proposal = &ctx.accounts.proposal;
+        require!(
+            matches!(proposal.status, ProposalStatus::Finalized | ProposalStatus::Executed),
+            PsephosError::ProposalNotSettled
+        );
+        let results = &ctx.accounts.results;
+        require!(proposal.pending_transactions(results) == 0, PsephosError::ExecutionPending);
+        let content = (&proposal.title, &proposal.options).try_to_vec()?;
+        if proposal.ballot_mode == BallotMode::Encrypted {
+            let encrypted_tally = ctx.accounts.encrypted_tally
+                .as_ref()
+                .ok_or(PsephosError::MissingEncryptedTally)?;
+            close_if_initialized(encrypted_tally, &ctx.accounts.creator)?;
+        }
+        if proposal.voting_method.is_ranked() {
+            let ranked_ballots = ctx.accounts.ranked_ballots
+                .as_ref()
+                .ok_or(PsephosError::MissingRankedBallots)?;
+            close_if_initialized(ranked_ballots, &ctx.accounts.creator)?;
+        }
+        close_transactions(proposal, ctx.remaining_accounts, &ctx.accounts.creator)?;
+
+        let archive = &mut ctx.accounts.archive;
+        archive.governance_space = proposal.governance_space;
+        archive.proposal_id = proposal.id;
+        archive.creator = proposal.creator;
+        archive.content_hash = hash(&content).to_bytes();
+        archive.ballot_mode = proposal.ballot_mode;
+        archive.voting_method = proposal.voting_method;
+        archive.end_time = proposal.end_time;
+        archive.vote_count = proposal.vote_count;
+        archive.total_weight = proposal.total_weight;
+        archive.tallies = results.tallies.clone();
+        archive.elected = results.elected.clone();
+        archive.outcome = results.outcome.ok_or(PsephosError::ProposalNotSettled)?;
+        archive.winning_option = results.winning_option;
+        archive.status = proposal.status;
+        archive.bump = ctx.bumps.archive;
+
+        emit!(ProposalArchived {
+            proposal: proposal.key(),
+            archive: archive.key(),
+            proposal_id: proposal.id,
+            outcome: archive.outcome,
+        });
+
+        msg!("Proposal {} archived", proposal.id);
+        Ok(())
+    }
+
+    /// Attach an instruction to run if `option` wins and the proposal passes
+    ///
+    /// Creator only, before the first vote, and not for single transferable
+    /// vote proposals, which have no single winning option. Transactions of
+    /// an option are numbered from 0 and executed in that order by
+    /// `execute_proposal`, signed by the space treasury PDA.
+    pub fn insert_transaction(
+        ctx: Context<InsertTransaction>,
+        option: u8,
+        instruction: ProposalInstruction,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let proposal = &mut ctx.accounts.proposal;
+        require!(proposal.vote_count == 0, PsephosError::VotingStarted);
+        require!(
+            matches!(
+                proposal.effective_status(clock.unix_timestamp),
+                ProposalStatus::Draft | ProposalStatus::Active
+            ),
+            PsephosError::VotingEnded
+        );
+        require!((option as usize) < proposal.options.len(), PsephosError::InvalidVoteChoice);
+        // STV elects several options rather than one winner to execute for
+        require!(
+            !matches!(proposal.voting_method, VotingMethod::SingleTransferableVote { .. }),
+            PsephosError::VotingMethodMismatch
+        );
+        // Re-entering psephos would act on stale account state
+        require!(
+            instruction.program_id != crate::ID
+                && instruction.accounts.len() <= MAX_TRANSACTION_ACCOUNTS
+                && instruction.data.len() <= MAX_TRANSACTION_DATA,
+            PsephosError::InvalidProposalTransaction
+        );
+
+        let index = proposal.transaction_counts[option as usize];
+        require!(index < MAX_TRANSACTIONS_PER_OPTION, PsephosError::InvalidProposalTransaction);
+        proposal.transaction_counts[option as usize] = index + 1;
+
+        let program_id = instruction.program_id;
+        let transaction = &mut ctx.accounts.proposal_transaction;
+        transaction.proposal = proposal.key();
+        transaction.option = option;
+        transaction.index = index;
+        transaction.instruction = instruction;
+        transaction.bump = ctx.bumps.proposal_transaction;
+
+        emit!(TransactionInserted {
+            proposal: proposal.key(),
+            option,
+            index,
+            program_id,
+        });
+
+        msg!("Transaction {} attached to option {} of proposal {}", index, option, proposal.id);
+        Ok(())
+    }
+
+    /// Run the next attached transaction of a passed proposal's winning option
+    ///
+    /// Anyone can call this once the proposal's timelock has elapsed. The
+    /// instruction's accounts are passed as remaining accounts; the space
+    /// treasury PDA signs the CPI, so it can move SOL from the treasury or
+    /// tokens from its vaults. The transaction account is then closed to the
+    /// creator. Once every transaction of the winning option has run, the
+    /// proposal moves to Executed.
+    pub fn execute_proposal<'info>(ctx: Context<'_, '_, 'info, 'info, ExecuteProposal<'info>>) -> Result<()> {
+        let clock = Clock::get()?;
+        let results = &ctx.accounts.results;
+        require!(results.outcome == Some(ProposalOutcome::Passed), PsephosError::ProposalNotPassed);
+        let executable_at = ctx.accounts.proposal.executable_at.ok_or(PsephosError::ProposalNotPassed)?;
+        require!(clock.unix_timestamp >= executable_at, PsephosError::TimelockNotElapsed);
+
+        let transaction = &ctx.accounts.proposal_transaction;
+        require!(results.winning_option == Some(transaction.option), PsephosError::ProposalNotPassed);
+        require!(transaction.index == ctx.accounts.proposal.executed_count, PsephosError::TransactionOutOfOrder);
+
+        let stored = &transaction.instruction;
+        let instruction = Instruction {
+            program_id: stored.program_id,
+            accounts: stored.accounts
+                .iter()
+                .map(|account| AccountMeta {
+                    pubkey: account.pubkey,
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: stored.data.clone(),
+        };
+        let mut account_infos = ctx.remaining_accounts.to_vec();
+        account_infos.push(ctx.accounts.treasury.to_account_info());
+
+        let space_key = ctx.accounts.proposal.governance_space;
+        let treasury_seeds: &[&[u8]] = &[b"treasury", space_key.as_ref(), &[ctx.bumps.treasury]];
+        invoke_signed(&instruction, &account_infos, &[treasury_seeds])?;
+
+        let transaction = &ctx.accounts.proposal_transaction;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.executed_count += 1;
+        if proposal.executed_count == proposal.transaction_counts[transaction.option as usize] {
+            proposal.transition(ProposalStatus::Executed, clock.unix_timestamp)?;
+        }
+
+        emit!(TransactionExecuted {
+            proposal: proposal.key(),
+            option: transaction.option,
+            index: transaction.index,
+            program_id: transaction.instruction.program_id,
+        });
+
+        msg!("Executed transaction {} of proposal {}", transaction.index, proposal.id);
+        Ok(())
+    }
+
+    /// Effective status of a proposal at the current clock
+    ///
+    /// Read-only view for clients (simulate the instruction to read the
+    /// return value); the stored `status` only advances when an instruction
+    /// touches the proposal.
+    pub fn proposal_status(ctx: Context<ViewProposal>) -> Result<ProposalStatus> {
+        let clock = Clock::get()?;
+        Ok(ctx.accounts.proposal.effective_status(clock.unix_timestamp))
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Verify a Gnark Groth16 proof against the pinned verifier
+///
+/// If `zk_verifier` is a psephos-owned `VerifyingKey` account, the proof is
+/// verified in-program with the alt_bn128 syscalls. Otherwise it is a Sunspot
+/// verifier program, which expects `proof_bytes || public_witness_bytes` as
+/// instruction data and takes no accounts. The caller is responsible for
+/// constraining `zk_verifier` to the verifier for the right circuit.
+fn verify_groth16_proof(zk_verifier: &AccountInfo, proof: &[u8], public_witness: &[u8]) -> Result<()> {
+    // When skip-zk-verify feature is enabled, skip verification (for testing with mock proofs)
+    #[cfg(not(feature = "skip-zk-verify"))]
+    {
+        if zk_verifier.owner == &crate::ID {
+            let verifying_key = VerifyingKey::try_deserialize(&mut &zk_verifier.try_borrow_data()?[..])?;
+            let proof = groth16::Groth16Proof::from_gnark(proof)?;
+            let witness = PublicWitness::parse(public_witness)?;
+
+            groth16::verify(&verifying_key, &proof, witness.elements())?;
+
+            msg!("ZK proof verified natively with alt_bn128 syscalls");
+            return Ok(());
+        }
+
+        let verify_ix = Instruction {
+            program_id: zk_verifier.key(),
+            accounts: vec![], // Verifier requires no accounts
+            data: [proof, public_witness].concat(),
+        };
+
+        invoke(&verify_ix, std::slice::from_ref(zk_verifier))?;
+
+        msg!("ZK proof cryptographically verified on-chain!");
+    }
+
+    #[cfg(feature = "skip-zk-verify")]
+    {
+        msg!("ZK proof verification SKIPPED (skip-zk-verify feature enabled)");
+        // Still use the arguments to avoid unused variable warnings
+        let _ = (zk_verifier.key(), proof, public_witness);
+    }
+
+    Ok(())
+}
+
+/// Check the proof size and that the public witness matches the proposal and
+/// the submitted nullifier and commitment
+///
+/// Returns the proven vote weight for weighted snapshot proposals.
+fn validate_vote_witness(
+    proposal: &Account<Proposal>,
+    nullifier: &[u8; 32],
+    vote_commitment: &[u8; 32],
+    proof: &[u8],
+    public_witness: &[u8],
+) -> Result<Option<u64>> {
+    // 1. Validate proof size (Gnark Groth16 proofs are 324-388 bytes)
+    require!(proof.len() >= MIN_PROOF_SIZE, PsephosError::InvalidProof);
+    require!(proof.len() <= GNARK_PROOF_SIZE + 64, PsephosError::InvalidProof);
+    
+    // 2. Parse the public witness strictly (exact header, no trailing bytes,
+    // canonical field elements)
+    // Quadratic snapshot proofs share the layout, proving the square root
+    let weighted_snapshot = proposal.eligibility_root.is_some()
+        && proposal.weighting != VoteWeighting::OnePerVoter;
+    let approval = proposal.voting_method == VotingMethod::Approval;
+    let ranked = proposal.voting_method.is_ranked();
+    let num_public_inputs = if weighted_snapshot {
+        NUM_WEIGHTED_SNAPSHOT_PUBLIC_INPUTS
+    } else if approval {
+        NUM_APPROVAL_PUBLIC_INPUTS
+    } else if ranked {
+        NUM_RANKED_PUBLIC_INPUTS
+    } else if proposal.eligibility_root.is_some() {
+        NUM_SNAPSHOT_PUBLIC_INPUTS
+    } else {
+        NUM_PUBLIC_INPUTS
+    };
+    let witness = PublicWitness::parse_expected(public_witness, num_public_inputs)?;
+
+    // 3. Verify public inputs match the proposal and submitted values
+    // Public inputs order: [min_threshold, proposal_domain, vote_commitment, nullifier]
+    // Snapshot proposals append: [eligibility_root]
+    // Weighted snapshot proposals then append: [weight_unit, weight_cap, weight]
+    // Approval and ranked proposals append: [num_options]
+    require!(witness.u64_at(0)? == proposal.min_threshold, PsephosError::ThresholdMismatch);
+    require!(
+        *witness.field(1)? == proposal_domain(&proposal.key()),
+        PsephosError::ProposalDomainMismatch
+    );
+    require!(witness.field(2)? == vote_commitment, PsephosError::CommitmentMismatch);
+    require!(witness.field(3)? == nullifier, PsephosError::NullifierMismatch);
+    if let Some(eligibility_root) = proposal.eligibility_root {
+        require!(*witness.field(4)? == eligibility_root, PsephosError::EligibilityRootMismatch);
+    }
+    if approval || ranked {
+        require!(witness.u64_at(4)? == proposal.options.len() as u64, PsephosError::OptionCountMismatch);
+    }
+    let proven_weight = match proposal.weighting {
+        VoteWeighting::TokenWeighted { unit, cap } if weighted_snapshot => {
+            require!(witness.u64_at(5)? == unit, PsephosError::WeightingMismatch);
+            require!(witness.u64_at(6)? == cap.unwrap_or(u64::MAX), PsephosError::WeightingMismatch);
+            Some(witness.u64_at(7)?)
+        }
+        _ => None,
+    };
+    
+    // Log proof validation success
+    msg!("ZK proof validated: {} bytes proof, {} bytes witness", proof.len(), public_witness.len());
+    msg!("Public inputs verified: threshold={}, proposal={}", proposal.min_threshold, proposal.id);
+
+    Ok(proven_weight)
+}
+
+/// Whether `keys` lists any account twice
+fn has_duplicate_keys(keys: &[Pubkey]) -> bool {
+    keys.iter().enumerate().any(|(i, key)| keys[..i].contains(key))
+}
+
+/// Check a space's admin set (at least one) and proposer allowlist
+fn validate_space_members(admins: &[Pubkey], proposers: &[Pubkey]) -> Result<()> {
+    require!(
+        !admins.is_empty() && admins.len() <= MAX_SPACE_ADMINS && !has_duplicate_keys(admins),
+        PsephosError::InvalidSpaceAdmins
+    );
+    require!(
+        proposers.len() <= MAX_SPACE_PROPOSERS && !has_duplicate_keys(proposers),
+        PsephosError::InvalidProposerList
+    );
+    Ok(())
+}
+
+/// Pay out a proposal's deposit, refunding the creator or slashing it to
+/// the space treasury; the deposit PDA keeps only its rent
+fn settle_deposit<'info>(
+    deposit: &mut Account<'info, ProposalDeposit>,
+    creator: &SystemAccount<'info>,
+    treasury: &SystemAccount<'info>,
+    slash: bool,
+) -> Result<()> {
+    let amount = deposit.amount;
+    let recipient = if slash { treasury } else { creator };
+    if amount > 0 {
+        deposit.amount = 0;
+        **deposit.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **recipient.to_account_info().try_borrow_mut_lamports()? += amount;
+    }
+
+    emit!(DepositSettled {
+        proposal: deposit.proposal,
+        amount,
+        recipient: recipient.key(),
+        slashed: slash,
+    });
+    Ok(())
+}
+
+/// Check the title and option limits shared by create and amend
+fn validate_title_and_options(title: &str, options: &[String]) -> Result<()> {
+    require!(title.len() <= MAX_TITLE_LENGTH, PsephosError::TitleTooLong);
+    require!(options.len() >= 2, PsephosError::TooFewOptions);
+    require!(options.len() <= MAX_OPTIONS, PsephosError::TooManyOptions);
+
+    for option in options {
+        require!(option.len() <= MAX_OPTION_LENGTH, PsephosError::OptionTooLong);
+    }
+    Ok(())
+}
+
+/// Validate a proposal's timing and compute (start, end, reveal end)
+///
+/// `start_time` defaults to `now` and may not be in the past. The voting
+/// period must lie within [`MIN_VOTING_PERIOD_SECONDS`,
+/// `MAX_VOTING_PERIOD_SECONDS`] and every timestamp must fit in an i64.
+fn voting_schedule(
+    now: i64,
+    start_time: Option<i64>,
+    voting_period_seconds: i64,
+    reveal_period_seconds: i64,
+) -> Result<(i64, i64, i64)> {
+    let start_time = start_time.unwrap_or(now);
+    require!(start_time >= now, PsephosError::StartTimeInPast);
+    require!(
+        (MIN_VOTING_PERIOD_SECONDS..=MAX_VOTING_PERIOD_SECONDS).contains(&voting_period_seconds),
+        PsephosError::InvalidVotingPeriod
+    );
+    require!(reveal_period_seconds > 0, PsephosError::InvalidRevealPeriod);
+
+    let end_time = start_time
+        .checked_add(voting_period_seconds)
+        .ok_or(PsephosError::InvalidVotingPeriod)?;
+    let reveal_end_time = end_time
+        .checked_add(reveal_period_seconds)
+        .ok_or(PsephosError::InvalidRevealPeriod)?;
+    Ok((start_time, end_time, reveal_end_time))
+}
+
+/// Close an account at one of a proposal's optional PDAs, sending its rent to
+/// `recipient`; does nothing if the account was never created
+fn close_if_initialized(account: &AccountInfo, recipient: &AccountInfo) -> Result<()> {
+    if account.owner != &crate::ID {
+        return Ok(());
+    }
+    let lamports = account.lamports();
+    **account.try_borrow_mut_lamports()? = 0;
+    **recipient.try_borrow_mut_lamports()? += lamports;
+    account.assign(&system_program::ID);
+    account.realloc(0, false)?;
+    Ok(())
+}
+
+/// Close the proposal's attached transactions that were never executed,
+/// passed as `accounts`, refunding their rent to `recipient`
+///
+/// Executed transactions are closed by `execute_proposal`; every other one
+/// must be passed, each once.
+fn close_transactions(proposal: &Account<Proposal>, accounts: &[AccountInfo], recipient: &AccountInfo) -> Result<()> {
+    let attached: usize = proposal.transaction_counts.iter().map(|count| *count as usize).sum();
+    require!(
+        accounts.len() == attached - proposal.executed_count as usize,
+        PsephosError::MissingProposalTransactions
+    );
+    for account in accounts {
+        // A repeated account no longer belongs to the program once closed
+        require!(account.owner == &crate::ID, PsephosError::InvalidProposalTransaction);
+        let transaction = ProposalTransaction::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+        require!(transaction.proposal == proposal.key(), PsephosError::InvalidProposalTransaction);
+        close_if_initialized(account, recipient)?;
+    }
+    Ok(())
+}
+
+/// Store a newly cast vote and count it and its weight on the proposal
+///
+/// Votes that carry no weight are rejected.
+#[allow(clippy::too_many_arguments)]
+fn record_vote(
+    proposal: &mut Account<Proposal>,
+    vote_record: &mut VoteRecord,
+    bump: u8,
+    refund_key: Pubkey,
+    nullifier: [u8; 32],
+    vote_commitment: [u8; 32],
+    weight: u64,
+    timestamp: i64,
+) -> Result<()> {
+    require!(weight > 0, PsephosError::InsufficientTokens);
+    vote_record.proposal = proposal.key();
+    vote_record.refund_key = refund_key;
+    vote_record.nullifier = nullifier;
+    vote_record.vote_commitment = vote_commitment;
+    vote_record.timestamp = timestamp;
+    vote_record.is_revealed = false;
+    vote_record.revealed_choice = None;
+    vote_record.weight = weight;
+    vote_record.bump = bump;
+
+    // Increment vote count
+    proposal.vote_count += 1;
+    proposal.total_weight = proposal.total_weight
+        .checked_add(weight)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    emit!(VoteCast {
+        proposal: proposal.key(),
+        nullifier,
+        vote_commitment,
+        timestamp,
+        vote_count: proposal.vote_count,
+        weight,
+    });
+    Ok(())
+}
+
+/// Common reveal preconditions shared by the reveal instructions
+fn check_reveal(proposal: &mut Proposal, vote_record: &VoteRecord) -> Result<()> {
+    let clock = Clock::get()?;
+
+    // Can only reveal after voting ends and before the reveal window closes
+    proposal.transition(ProposalStatus::Revealing, clock.unix_timestamp)?;
+    require!(clock.unix_timestamp <= proposal.reveal_end_time, PsephosError::RevealWindowClosed);
+    require!(proposal.ballot_mode == BallotMode::CommitReveal, PsephosError::BallotModeMismatch);
+    require!(!vote_record.is_revealed, PsephosError::AlreadyRevealed);
+    Ok(())
+}
+
+/// Check a single-value ballot (an option, or an approval bitmask); ranked
+/// ballots are revealed with `reveal_ranked_vote`
+fn check_ballot(proposal: &Proposal, vote_choice: u16) -> Result<()> {
+    require!(!proposal.voting_method.is_ranked(), PsephosError::VotingMethodMismatch);
+    require!(
+        proposal.voting_method.is_valid_ballot(vote_choice, proposal.options.len()),
+        PsephosError::InvalidVoteChoice
+    );
+    Ok(())
+}
+
+/// Mark a vote as revealed and add its weight to the tally
+fn apply_reveal(
+    proposal: &mut Proposal,
+    vote_record: &mut VoteRecord,
+    results: &mut ProposalResults,
+    vote_choice: u16,
+) {
+    proposal.revealed_count += 1;
+    vote_record.is_revealed = true;
+    vote_record.revealed_choice = Some(vote_choice);
+    proposal.voting_method.count_ballot(&mut results.tallies, vote_choice, vote_record.weight);
+    results.counted_weight += vote_record.weight;
+
+    emit!(VoteRevealed {
+        proposal: vote_record.proposal,
+        nullifier: vote_record.nullifier,
+        vote_choice,
+        weight: vote_record.weight,
+    });
+}
+
+/// Encode a u64 as a 32-byte big-endian BN254 field element
+pub fn u64_to_field(value: u64) -> [u8; 32] {
+    let mut field = [0u8; 32];
+    field[24..].copy_from_slice(&value.to_be_bytes());
+    field
+}
+
+/// A proposal's domain in the circuits: its address reduced into the BN254
+/// scalar field
+///
+/// Nullifiers and vote commitments are hashed under the domain rather than
+/// the proposal ID, which restarts at 1 in every space. A voter's nullifiers
+/// therefore cannot be linked across spaces, and a proof made for one
+/// proposal is rejected by every other.
+pub fn proposal_domain(proposal: &Pubkey) -> [u8; 32] {
+    groth16::reduce_scalar(proposal.as_ref())
+}
+
+/// Compute the vote commitment poseidon(choice, secret, proposal_domain)
+///
+/// `vote_choice` is the option index, the bitmask for approval ballots or
+/// the packed ranking for ranked ballots.
+///
+/// Matches `poseidon::bn254::hash_3` in the Noir circuit (circom-compatible
+/// BN254 x^5 parameters, big-endian field elements). A secret that is not a
+/// canonical field element cannot open any commitment and fails the reveal.
+pub fn compute_vote_commitment(
+    vote_choice: u64,
+    voter_secret: &[u8; 32],
+    proposal_domain: &[u8; 32],
+) -> Result<[u8; 32]> {
+    let choice = u64_to_field(vote_choice);
+    let hash = hashv(
+        Parameters::Bn254X5,
+        Endianness::BigEndian,
+        &[&choice, voter_secret, proposal_domain],
+    )
+    .map_err(|_| PsephosError::InvalidReveal)?;
+    Ok(hash.to_bytes())
+}
+
+// ============================================================================
+// Account Structures
+// ============================================================================
+
+#[account]
+#[derive(InitSpace)]
+pub struct GovernanceSpace {
+    /// Account that created the space; part of its PDA seeds
+    pub creator: Pubkey,
+    /// Name of the space, unique per creator
+    #[max_len(MAX_SPACE_NAME_LENGTH)]
+    pub name: String,
+    /// Accounts allowed to update the space and cancel spam proposals
+    #[max_len(MAX_SPACE_ADMINS)]
+    pub admins: Vec<Pubkey>,
+    /// Accounts allowed to create proposals in a gated space
+    #[max_len(MAX_SPACE_PROPOSERS)]
+    pub proposers: Vec<Pubkey>,
+    /// Settings copied into each new proposal
+    pub config: SpaceConfig,
+    /// Number of proposals created; the latest proposal's ID
+    pub proposal_count: u64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl GovernanceSpace {
+    /// ID the next proposal in this space is created with (IDs start at 1)
+    pub fn next_proposal_id(&self) -> u64 {
+        self.proposal_count + 1
+    }
+
+    /// Whether `key` is one of the space's admins
+    pub fn is_admin(&self, key: &Pubkey) -> bool {
+        self.admins.contains(key)
+    }
+
+    /// Whether `proposer` may create proposals, given their balance of the
+    /// space's token mint if they passed a token account
+    ///
+    /// A space with no allowlist and no minimum balance is open.
+    pub fn can_propose(&self, proposer: &Pubkey, balance: Option<u64>) -> bool {
+        if self.proposers.is_empty() && self.config.proposer_min_balance.is_none() {
+            return true;
+        }
+        if self.proposers.contains(proposer) {
+            return true;
+        }
+        match (self.config.proposer_min_balance, balance) {
+            (Some(min_balance), Some(balance)) => balance >= min_balance,
+            _ => false,
+        }
+    }
+}
+
+/// Proposal settings a space applies to its proposals
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct SpaceConfig {
+    /// Token mint for eligibility checks
+    pub token_mint: Pubkey,
+    /// Minimum token balance required to vote
+    pub min_threshold: u64,
+    /// Minimum participation for a result to count
+    pub quorum: Option<Quorum>,
+    /// Share of counted votes the winning option needs to pass
+    pub approval_threshold: ApprovalThreshold,
+    /// Default voting period (seconds)
+    pub voting_period_seconds: i64,
+    /// Default reveal period (seconds)
+    pub reveal_period_seconds: i64,
+    /// Token balance that lets a non-allowlisted account create proposals
+    pub proposer_min_balance: Option<u64>,
+    /// Lamports a creator deposits with each proposal
+    pub proposal_deposit: u64,
+    /// Delay between a proposal passing and its transactions running
+    pub execution_delay_seconds: i64,
+}
+
+impl SpaceConfig {
+    /// Check the thresholds, default periods, deposit and timelock
+    pub fn validate(&self) -> Result<()> {
+        if let Some(quorum) = self.quorum {
+            require!(quorum.is_valid(), PsephosError::InvalidQuorum);
+        }
+        require!(self.approval_threshold.is_valid(), PsephosError::InvalidApprovalThreshold);
+        require!(
+            (MIN_VOTING_PERIOD_SECONDS..=MAX_VOTING_PERIOD_SECONDS).contains(&self.voting_period_seconds),
+            PsephosError::InvalidVotingPeriod
+        );
+        require!(self.reveal_period_seconds > 0, PsephosError::InvalidRevealPeriod);
+        require!(self.execution_delay_seconds >= 0, PsephosError::InvalidExecutionDelay);
+        // Smaller deposits could not be paid into an empty treasury
+        require!(
+            self.proposal_deposit == 0 || self.proposal_deposit >= Rent::get()?.minimum_balance(0),
+            PsephosError::InvalidProposalDeposit
+        );
+        Ok(())
+    }
+
+    /// Check that the quorum can be met by proposals counted with `weighting`
+    pub fn validate_weighting(&self, weighting: VoteWeighting) -> Result<()> {
+        if let Some(quorum) = self.quorum {
+            require!(quorum.supports(weighting), PsephosError::QuorumNeedsTokenWeighting);
+        }
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal {
+    /// Governance space the proposal belongs to
+    pub governance_space: Pubkey,
+    /// Sequential ID within the space
+    pub id: u64,
+    /// Creator of the proposal
+    pub creator: Pubkey,
+    /// Title of the proposal
+    #[max_len(MAX_TITLE_LENGTH)]
+    pub title: String,
+    /// Voting options
+    #[max_len(MAX_OPTIONS, MAX_OPTION_LENGTH)]
+    pub options: Vec<String>,
+    /// Token mint for eligibility check
+    pub token_mint: Pubkey,
+    /// Minimum token balance required to vote
+    pub min_threshold: u64,
+    /// Merkle root of the token-holder snapshot; when set, eligibility is
+    /// proven in the circuit instead of via a voter token account
+    pub eligibility_root: Option<[u8; 32]>,
+    /// Verifier program pinned from the registry at creation
+    pub zk_verifier: Pubkey,
+    /// Reveal circuit verifier pinned from the registry at creation; without
+    /// one, votes can only be revealed with `reveal_vote`
+    pub reveal_verifier: Option<Pubkey>,
+    /// How ballots are recorded and counted
+    pub ballot_mode: BallotMode,
+    /// How much each vote counts
+    pub weighting: VoteWeighting,
+    /// How each vote's weight is counted toward its option
+    pub voting_method: VotingMethod,
+    /// Minimum participation for the result to count
+    pub quorum: Option<Quorum>,
+    /// Share of counted votes the winning option needs to pass
+    pub approval_threshold: ApprovalThreshold,
+    /// Voting start time (Unix timestamp)
+    pub start_time: i64,
+    /// Voting end time (Unix timestamp)
+    pub end_time: i64,
+    /// End of the reveal window (Unix timestamp)
+    pub reveal_end_time: i64,
+    /// Number of votes cast
+    pub vote_count: u64,
+    /// Sum of the weights of the votes cast
+    pub total_weight: u64,
+    /// Number of votes revealed and added to the tally
+    pub revealed_count: u64,
+    /// Lamports held above rent, paid to whoever finalizes
+    pub finalize_bounty: u64,
+    /// Timelock between passing and execution, copied from the space
+    pub execution_delay_seconds: i64,
+    /// When the winning option's transactions may run; set if the proposal passes
+    pub executable_at: Option<i64>,
+    /// Number of attached transactions per option
+    #[max_len(MAX_OPTIONS)]
+    pub transaction_counts: Vec<u8>,
+    /// Number of the winning option's transactions executed so far
+    pub executed_count: u8,
+    /// Lifecycle status, advanced through `Proposal::transition`
+    pub status: ProposalStatus,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl Proposal {
+    /// Counted weight of a vote backed by `balance` tokens
+    pub fn vote_weight(&self, balance: u64) -> u64 {
+        self.voting_method.effective_weight(self.weighting.weight(balance))
+    }
+
+    /// Status after applying the clock-driven phases to the stored status
+    ///
+    /// Draft becomes Active at `start_time`, and Draft or Active becomes
+    /// Revealing once `end_time` has passed. Every other status only changes
+    /// through an explicit transition.
+    pub fn effective_status(&self, now: i64) -> ProposalStatus {
+        match self.status {
+            ProposalStatus::Draft | ProposalStatus::Active if now > self.end_time => ProposalStatus::Revealing,
+            ProposalStatus::Draft if now >= self.start_time => ProposalStatus::Active,
+            status => status,
+        }
+    }
+
+    /// Move the proposal to `next`, the single entry point for status changes
+    ///
+    /// Instructions that act within a phase transition to the phase they
+    /// need (e.g. `cast_vote` to Active), which syncs the stored status with
+    /// the clock and fails with the error describing why the phase is
+    /// unavailable.
+    pub fn transition(&mut self, next: ProposalStatus, now: i64) -> Result<()> {
+        let current = self.effective_status(now);
+        if !current.can_transition_to(next) {
+            return Err(current.transition_error(next).into());
+        }
+        self.status = next;
+        Ok(())
+    }
+
+    /// Number of the winning option's transactions still to execute
+    pub fn pending_transactions(&self, results: &ProposalResults) -> u8 {
+        match (results.outcome, results.winning_option) {
+            (Some(ProposalOutcome::Passed), Some(option)) => {
+                self.transaction_counts[option as usize].saturating_sub(self.executed_count)
+            }
+            _ => 0,
+        }
+    }
+
+    /// Replace the voting schedule of a proposal that has not closed
+    ///
+    /// The proposal goes back to Draft and the clock takes it to Active at
+    /// the new `start_time`.
+    pub fn reschedule(&mut self, start_time: i64, end_time: i64, reveal_end_time: i64, now: i64) -> Result<()> {
+        let current = self.effective_status(now);
+        if !matches!(current, ProposalStatus::Draft | ProposalStatus::Active) {
+            return Err(current.transition_error(ProposalStatus::Active).into());
+        }
+        self.start_time = start_time;
+        self.end_time = end_time;
+        self.reveal_end_time = reveal_end_time;
+        self.status = ProposalStatus::Draft;
+        Ok(())
+    }
+}
+
+/// Lifecycle of a proposal
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum ProposalStatus {
+    /// Created, voting has not started
+    Draft,
+    /// Accepting votes
+    Active,
+    /// Voting has ended; votes can be revealed until `reveal_end_time` and
+    /// the tally decrypted
+    Revealing,
+    /// Results are final
+    Finalized,
+    /// Withdrawn by the creator
+    Cancelled,
+    /// Finalized and its effects executed
+    Executed,
+}
+
+impl ProposalStatus {
+    /// Whether an instruction may move a proposal from `self` to `next`
+    ///
+    /// Staying in a phase is allowed; Draft -> Active -> Revealing are
+    /// driven by the clock only (see `Proposal::effective_status`).
+    pub fn can_transition_to(self, next: ProposalStatus) -> bool {
+        use ProposalStatus::*;
+        matches!(
+            (self, next),
+            (Draft, Draft)
+                | (Draft, Cancelled)
+                | (Active, Active)
+                | (Active, Cancelled)
+                | (Revealing, Revealing)
+                | (Revealing, Finalized)
+                | (Finalized, Executed)
+        )
+    }
+
+    /// Error for a rejected transition from `self` to `next`
+    fn transition_error(self, next: ProposalStatus) -> PsephosError {
+        use ProposalStatus::*;
+        match (self, next) {
+            (Cancelled, _) => PsephosError::ProposalCancelled,
+            (Finalized | Executed, Active | Revealing | Finalized) => PsephosError::ProposalFinalized,
+            (Draft, Active) => PsephosError::VotingNotStarted,
+            (Revealing, Active) => PsephosError::VotingEnded,
+            (Draft | Active, Revealing | Finalized) => PsephosError::VotingNotEnded,
+            _ => PsephosError::InvalidStatusTransition,
+        }
+    }
+}
+
+/// How a proposal's ballots are recorded and counted
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum BallotMode {
+    /// Vote commitments opened by each voter after voting ends
+    CommitReveal,
+    /// ElGamal ciphertexts tallied homomorphically and threshold-decrypted
+    Encrypted,
+}
+
+/// Public inputs a registered verifier's circuit exposes
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum PublicInputLayout {
+    /// [min_threshold, proposal_domain, vote_commitment, nullifier] (circuits/src)
+    Eligibility,
+    /// Eligibility inputs followed by [eligibility_root] (circuits/snapshot)
+    Snapshot,
+    /// Snapshot inputs followed by [weight_unit, weight_cap, weight]
+    /// (circuits/weighted)
+    WeightedSnapshot,
+    /// Weighted snapshot inputs with `weight` the square root of the token
+    /// weight (circuits/quadratic)
+    QuadraticSnapshot,
+    /// Eligibility inputs followed by [num_options], committing to an
+    /// approval bitmask (circuits/approval)
+    ApprovalEligibility,
+    /// Eligibility inputs followed by [num_options], committing to a packed
+    /// ranking (circuits/ranked)
+    RankedEligibility,
+    /// [min_threshold, proposal_domain, ballot_hash, nullifier] (circuits/encrypted)
+    EncryptedBallot,
+    /// [proposal_domain, vote_choice, vote_commitment] (circuits/reveal)
+    Reveal,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct VerifierEntry {
+    /// Sunspot verifier program ID, or a `VerifyingKey` account address
+    pub program_id: Pubkey,
+    /// Version of the circuit the verifier was generated from
+    pub circuit_version: u32,
+    /// Public-input layout of the circuit
+    pub layout: PublicInputLayout,
+    /// Whether new proposals may pin this verifier
+    pub is_active: bool,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct VerifyingKey {
+    /// Identifier chosen by the registry admin (PDA seed)
+    pub key_id: u64,
+    /// alpha in G1
+    pub alpha_g1: [u8; 64],
+    /// beta in G2
+    pub beta_g2: [u8; 128],
+    /// gamma in G2
+    pub gamma_g2: [u8; 128],
+    /// delta in G2
+    pub delta_g2: [u8; 128],
+    /// Public input commitments in G1 (constant term first, then one per
+    /// public input, then one for the BSB22 commitment hash if any)
+    #[max_len(MAX_VK_PUBLIC_INPUTS + 2)]
+    pub ic: Vec<[u8; 64]>,
+    /// Pedersen key for circuits whose proofs carry a BSB22 commitment
+    pub commitment_key: Option<CommitmentKey>,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// gnark Pedersen verifying key of a circuit's BSB22 commitment
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct CommitmentKey {
+    /// G in G2
+    pub g: [u8; 128],
+    /// -G / sigma in G2
+    pub g_root_sigma_neg: [u8; 128],
+    /// Public inputs hashed with the commitment, as 0-based indices
+    /// (gnark's `PublicAndCommitmentCommitted` minus one)
+    #[max_len(MAX_VK_PUBLIC_INPUTS)]
+    pub committed_inputs: Vec<u8>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct VerifierRegistry {
+    /// Authority allowed to add and deactivate verifiers
+    pub admin: Pubkey,
+    /// Registered verifiers
+    #[max_len(MAX_VERIFIERS)]
+    pub verifiers: Vec<VerifierEntry>,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl VerifierRegistry {
+    /// Look up a registered verifier by program ID
+    pub fn find(&self, program_id: &Pubkey) -> Option<&VerifierEntry> {
+        self.verifiers.iter().find(|entry| entry.program_id == *program_id)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct VoteRecord {
+    /// The proposal this vote is for
+    pub proposal: Pubkey,
+    /// Nullifier hash - unique per voter per proposal
+    pub nullifier: [u8; NULLIFIER_SIZE],
+    /// Commitment to the vote choice
+    pub vote_commitment: [u8; COMMITMENT_SIZE],
+    /// When the vote was cast
+    pub timestamp: i64,
+    /// Whether the vote has been revealed
+    pub is_revealed: bool,
+    /// The revealed vote choice, approval bitmask or first preference (only
+    /// set after reveal)
+    pub revealed_choice: Option<u16>,
+    /// Account that paid the rent and may close the record
+    pub refund_key: Pubkey,
+    /// Weight added to the tally when revealed
+    pub weight: u64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProposalDeposit {
+    /// The proposal the deposit was posted for
+    pub proposal: Pubkey,
+    /// Lamports deposited above rent; zero once refunded or slashed
+    pub amount: u64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RelayerPool {
+    /// The proposal whose relayed votes this pool pays for
+    pub proposal: Pubkey,
+    /// Proposal creator who set the pool up; receives what is left on close
+    pub creator: Pubkey,
+    /// Lamports paid to the relayer for each relayed vote
+    pub fee_per_vote: u64,
+    /// Total lamports paid out to relayers
+    pub total_paid: u64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct EncryptedTally {
+    /// The encrypted-ballot proposal this tally is for
+    pub proposal: Pubkey,
+    /// Joint ElGamal election key H
+    pub election_key: [u8; 64],
+    /// Trustee public key shares; trustee i evaluates the sharing at i + 1
+    #[max_len(MAX_TRUSTEES)]
+    pub trustee_keys: Vec<[u8; 64]>,
+    /// Number of trustee shares needed to decrypt
+    pub threshold: u8,
+    /// Homomorphic sum of every cast ballot
+    pub aggregate: Ciphertext,
+    /// Verified decryption shares, indexed like `trustee_keys`
+    #[max_len(MAX_TRUSTEES)]
+    pub decryption_shares: Vec<Option<[u8; 64]>>,
+    /// Whether the tally has been decrypted into ProposalResults
+    pub is_decrypted: bool,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProposalResults {
+    /// The proposal these results are for
+    pub proposal: Pubkey,
+    /// Vote tallies per option (final once the proposal is Finalized)
+    #[max_len(MAX_OPTIONS)]
+    pub tallies: Vec<u64>,
+    /// Weight of the ballots in the tallies; an approval ballot counts once
+    /// however many options it approves
+    pub counted_weight: u64,
+    /// Outcome computed at finalize
+    pub outcome: Option<ProposalOutcome>,
+    /// Option with the strictly highest tally, set at finalize (never for
+    /// single transferable vote, see `elected`)
+    pub winning_option: Option<u8>,
+    /// Options eliminated by the ranked tally crank (bitmask)
+    pub eliminated: u16,
+    /// Options elected by single transferable vote, in order of election
+    #[max_len(MAX_OPTIONS)]
+    pub elected: Vec<u8>,
+    /// Next ranked ballot the crank counts in the current round
+    pub tally_cursor: u32,
+    /// Current round's tallies in fixed-point units of `1 / ranked::SCALE`
+    #[max_len(MAX_OPTIONS)]
+    pub round_tallies: Vec<u128>,
+    /// Current round's weight of ballots with no remaining preference, in
+    /// the same units
+    pub round_exhausted: u128,
+    /// STV Droop quota in the same units, set after the first round
+    pub quota: u128,
+    /// Surplus of the option elected last round, passed on while the
+    /// current round is counted
+    pub transfer: Option<SurplusTransfer>,
+    /// Completed ranked tally rounds
+    #[max_len(MAX_OPTIONS)]
+    pub rounds: Vec<RankedRound>,
+    /// Whether the ranked tally crank has finished
+    pub tally_complete: bool,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// One ranked tally round as recorded by `tally_round`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub struct RankedRound {
+    /// Weight counted for each option, rounded down to whole units
+    #[max_len(MAX_OPTIONS)]
+    pub tallies: Vec<u64>,
+    /// Weight of ballots with no remaining preference, rounded down
+    pub exhausted_weight: u64,
+    /// Option eliminated after the round
+    pub eliminated: Option<u8>,
+    /// Option elected by quota after the round (STV)
+    pub elected: Option<u8>,
+}
+
+/// An STV surplus waiting to be passed on
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct SurplusTransfer {
+    /// The elected option
+    pub option: u8,
+    /// Share of each of its ballots' value passed on, out of `ranked::SCALE`
+    pub ratio: u64,
+}
+
+/// Revealed ballots of a ranked-choice proposal
+///
+/// Fixed-size entries (see `ranked`) follow the header at `ENTRIES_OFFSET`;
+/// the account grows by one entry per reveal.
+#[account]
+#[derive(InitSpace)]
+pub struct RankedBallots {
+    /// The proposal the ballots are for
+    pub proposal: Pubkey,
+    /// Number of entries after the header
+    pub ballot_count: u32,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl ProposalResults {
+    /// Options out of a ranked tally's race: eliminated or already elected
+    pub fn excluded(&self) -> u16 {
+        self.elected.iter().fold(self.eliminated, |mask, option| mask | 1 << option)
+    }
+}
+
+impl RankedBallots {
+    /// Byte offset of the first ballot entry
+    pub const ENTRIES_OFFSET: usize = 8 + Self::INIT_SPACE;
+}
+
+/// An instruction attached to a proposal, as signed and run by the treasury
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub struct ProposalInstruction {
+    /// Program to invoke
+    pub program_id: Pubkey,
+    /// Accounts in instruction order
+    #[max_len(MAX_TRANSACTION_ACCOUNTS)]
+    pub accounts: Vec<ProposalAccountMeta>,
+    /// Instruction data
+    #[max_len(MAX_TRANSACTION_DATA)]
+    pub data: Vec<u8>,
+}
+
+/// Serializable `AccountMeta`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct ProposalAccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProposalTransaction {
+    /// The proposal this transaction belongs to
+    pub proposal: Pubkey,
+    /// Option whose win triggers the transaction
+    pub option: u8,
+    /// Execution order within the option
+    pub index: u8,
+    /// The instruction to run
+    pub instruction: ProposalInstruction,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Compact record of an archived proposal
+#[account]
+#[derive(InitSpace)]
+pub struct ProposalArchive {
+    /// Space the proposal belonged to
+    pub governance_space: Pubkey,
+    /// ID of the proposal within the space
+    pub proposal_id: u64,
+    /// Creator of the proposal
+    pub creator: Pubkey,
+    /// SHA-256 of the Borsh-encoded (title, options)
+    pub content_hash: [u8; 32],
+    /// How ballots were recorded and counted
+    pub ballot_mode: BallotMode,
+    /// How vote weights were counted toward the tallies
+    pub voting_method: VotingMethod,
+    /// Voting end time (Unix timestamp)
+    pub end_time: i64,
+    /// Number of votes cast
+    pub vote_count: u64,
+    /// Sum of the weights of the votes cast
+    pub total_weight: u64,
+    /// Final tallies per option
+    #[max_len(MAX_OPTIONS)]
+    pub tallies: Vec<u64>,
+    /// Options elected by single transferable vote, in order of election
+    #[max_len(MAX_OPTIONS)]
+    pub elected: Vec<u8>,
+    /// Outcome computed at finalize
+    pub outcome: ProposalOutcome,
+    /// Option with the strictly highest tally
+    pub winning_option: Option<u8>,
+    /// Status when archived (Finalized or Executed)
+    pub status: ProposalStatus,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+// ============================================================================
+// Instruction Contexts
+// ============================================================================
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreateSpace<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + GovernanceSpace::INIT_SPACE,
+        seeds = [b"space", creator.key().as_ref(), name.as_bytes()],
+        bump
+    )]
+    pub governance_space: Account<'info, GovernanceSpace>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateSpace<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"space", governance_space.creator.as_ref(), governance_space.name.as_bytes()],
+        bump = governance_space.bump,
+        constraint = governance_space.is_admin(&admin.key()) @ PsephosError::Unauthorized
+    )]
+    pub governance_space: Account<'info, GovernanceSpace>,
+}
+
+#[derive(Accounts)]
+pub struct DepositToTreasury<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    #[account(
+        seeds = [b"space", governance_space.creator.as_ref(), governance_space.name.as_bytes()],
+        bump = governance_space.bump
+    )]
+    pub governance_space: Account<'info, GovernanceSpace>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury", governance_space.key().as_ref()],
+        bump
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitTreasuryVault<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [b"space", governance_space.creator.as_ref(), governance_space.name.as_bytes()],
+        bump = governance_space.bump
+    )]
+    pub governance_space: Account<'info, GovernanceSpace>,
+
+    #[account(
+        seeds = [b"treasury", governance_space.key().as_ref()],
+        bump
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"treasury_vault", governance_space.key().as_ref(), mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = treasury,
+        token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DepositTokensToTreasury<'info> {
+    pub depositor: Signer<'info>,
+
+    #[account(
+        seeds = [b"space", governance_space.creator.as_ref(), governance_space.name.as_bytes()],
+        bump = governance_space.bump
+    )]
+    pub governance_space: Account<'info, GovernanceSpace>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = depositor,
+        token::token_program = token_program
+    )]
+    pub depositor_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_vault", governance_space.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"space", governance_space.creator.as_ref(), governance_space.name.as_bytes()],
+        bump = governance_space.bump
+    )]
+    pub governance_space: Account<'info, GovernanceSpace>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + Proposal::INIT_SPACE,
+        seeds = [b"proposal", governance_space.key().as_ref(), governance_space.next_proposal_id().to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + ProposalResults::INIT_SPACE,
+        seeds = [b"results", governance_space.key().as_ref(), governance_space.next_proposal_id().to_le_bytes().as_ref()],
+        bump
+    )]
+    pub results: Account<'info, ProposalResults>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + ProposalDeposit::INIT_SPACE,
+        seeds = [b"deposit", proposal.key().as_ref()],
+        bump
+    )]
+    pub deposit: Account<'info, ProposalDeposit>,
+
+    /// Creator's token account, needed in a space gated by token balance
+    #[account(
+        constraint = creator_token_account.mint == governance_space.config.token_mint @ PsephosError::InvalidTokenMint,
+        constraint = creator_token_account.owner == creator.key() @ PsephosError::InvalidTokenOwner,
+    )]
+    pub creator_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"verifier_registry"],
+        bump = verifier_registry.bump
+    )]
+    pub verifier_registry: Account<'info, VerifierRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitVerifierRegistry<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + VerifierRegistry::INIT_SPACE,
+        seeds = [b"verifier_registry"],
+        bump
+    )]
+    pub verifier_registry: Account<'info, VerifierRegistry>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Psephos>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ PsephosError::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(key_id: u64)]
+pub struct InitVerifyingKey<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"verifier_registry"],
+        bump = verifier_registry.bump,
+        constraint = verifier_registry.admin == admin.key() @ PsephosError::Unauthorized
+    )]
+    pub verifier_registry: Account<'info, VerifierRegistry>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + VerifyingKey::INIT_SPACE,
+        seeds = [b"verifying_key", key_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub verifying_key: Account<'info, VerifyingKey>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateVerifierRegistry<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"verifier_registry"],
+        bump = verifier_registry.bump,
+        constraint = verifier_registry.admin == admin.key() @ PsephosError::Unauthorized
+    )]
+    pub verifier_registry: Account<'info, VerifierRegistry>,
+}
+
+#[derive(Accounts)]
+#[instruction(nullifier: [u8; 32], vote_commitment: [u8; 32], proof: Vec<u8>, public_witness: Vec<u8>)]
+pub struct CastVote<'info> {
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + VoteRecord::INIT_SPACE,
+        seeds = [b"vote", proposal.key().as_ref(), nullifier.as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    /// Voter's SPL token account for eligibility verification
+    /// (omitted for snapshot proposals with an eligibility root)
+    #[account(
+        constraint = voter_token_account.mint == proposal.token_mint @ PsephosError::InvalidTokenMint,
+        constraint = voter_token_account.owner == voter.key() @ PsephosError::InvalidTokenOwner,
+    )]
+    pub voter_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// ZK Verifier program for on-chain proof verification
+    /// CHECK: Sunspot verifier program or VerifyingKey account, validated by address constraint
+    #[account(
+        constraint = zk_verifier.key() == proposal.zk_verifier @ PsephosError::InvalidVerifierProgram
+    )]
+    pub zk_verifier: AccountInfo<'info>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(nullifier: [u8; 32], vote_commitment: [u8; 32], proof: Vec<u8>, public_witness: Vec<u8>)]
+pub struct CastVoteRelayed<'info> {
+    /// Any fee payer; carries no voter identity
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        init,
+        payer = relayer,
+        space = 8 + VoteRecord::INIT_SPACE,
+        seeds = [b"vote", proposal.key().as_ref(), nullifier.as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    /// Optional pool that reimburses the relayer
+    #[account(
+        mut,
+        seeds = [b"relayer_pool", proposal.key().as_ref()],
+        bump = relayer_pool.bump
+    )]
+    pub relayer_pool: Option<Account<'info, RelayerPool>>,
+
+    /// ZK Verifier program for on-chain proof verification
+    /// CHECK: Sunspot verifier program or VerifyingKey account, validated by address constraint
+    #[account(
+        constraint = zk_verifier.key() == proposal.zk_verifier @ PsephosError::InvalidVerifierProgram
+    )]
+    pub zk_verifier: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitRelayerPool<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        seeds = [b"proposal", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+        constraint = proposal.creator == creator.key() @ PsephosError::Unauthorized
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + RelayerPool::INIT_SPACE,
+        seeds = [b"relayer_pool", proposal.key().as_ref()],
+        bump
+    )]
+    pub relayer_pool: Account<'info, RelayerPool>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundRelayerPool<'info> {
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"relayer_pool", relayer_pool.proposal.as_ref()],
+        bump = relayer_pool.bump
+    )]
+    pub relayer_pool: Account<'info, RelayerPool>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseRelayerPool<'info> {
+    #[account(mut, address = relayer_pool.creator @ PsephosError::Unauthorized)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        close = creator,
+        seeds = [b"relayer_pool", relayer_pool.proposal.as_ref()],
+        bump = relayer_pool.bump
+    )]
+    pub relayer_pool: Account<'info, RelayerPool>,
+
+    /// CHECK: the pool's proposal; read only while it still exists, as its
+    /// status gates the close
+    #[account(address = relayer_pool.proposal)]
+    pub proposal: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitEncryptedTally<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        seeds = [b"proposal", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+        constraint = proposal.creator == creator.key() @ PsephosError::Unauthorized
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + EncryptedTally::INIT_SPACE,
+        seeds = [b"encrypted_tally", proposal.key().as_ref()],
+        bump
+    )]
+    pub encrypted_tally: Account<'info, EncryptedTally>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(nullifier: [u8; 32])]
+pub struct CastEncryptedVote<'info> {
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + VoteRecord::INIT_SPACE,
+        seeds = [b"vote", proposal.key().as_ref(), nullifier.as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    #[account(
+        mut,
+        seeds = [b"encrypted_tally", proposal.key().as_ref()],
+        bump = encrypted_tally.bump
+    )]
+    pub encrypted_tally: Account<'info, EncryptedTally>,
+
+    /// Voter's SPL token account for eligibility verification
+    #[account(
+        constraint = voter_token_account.mint == proposal.token_mint @ PsephosError::InvalidTokenMint,
+        constraint = voter_token_account.owner == voter.key() @ PsephosError::InvalidTokenOwner,
+    )]
+    pub voter_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// ZK Verifier program for on-chain proof verification
+    /// CHECK: Sunspot verifier program or VerifyingKey account, validated by address constraint
+    #[account(
+        constraint = zk_verifier.key() == proposal.zk_verifier @ PsephosError::InvalidVerifierProgram
+    )]
+    pub zk_verifier: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitDecryptionShare<'info> {
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        mut,
+        seeds = [b"encrypted_tally", proposal.key().as_ref()],
+        bump = encrypted_tally.bump
+    )]
+    pub encrypted_tally: Account<'info, EncryptedTally>,
+}
+
+#[derive(Accounts)]
+pub struct DecryptTally<'info> {
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        mut,
+        seeds = [b"encrypted_tally", proposal.key().as_ref()],
+        bump = encrypted_tally.bump
+    )]
+    pub encrypted_tally: Account<'info, EncryptedTally>,
+
+    #[account(
+        mut,
+        seeds = [b"results", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = results.bump
+    )]
+    pub results: Account<'info, ProposalResults>,
+}
+
+#[derive(Accounts)]
+pub struct RevealVote<'info> {
+    #[account(mut)]
+    pub revealer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        mut,
+        seeds = [b"vote", proposal.key().as_ref(), vote_record.nullifier.as_ref()],
+        bump = vote_record.bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    #[account(
+        mut,
+        seeds = [b"results", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = results.bump
+    )]
+    pub results: Account<'info, ProposalResults>,
+}
+
+#[derive(Accounts)]
+pub struct RevealVoteWithProof<'info> {
+    #[account(mut)]
+    pub revealer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        mut,
+        seeds = [b"vote", proposal.key().as_ref(), vote_record.nullifier.as_ref()],
+        bump = vote_record.bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    #[account(
+        mut,
+        seeds = [b"results", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = results.bump
+    )]
+    pub results: Account<'info, ProposalResults>,
+
+    /// ZK Verifier program for the reveal circuit
+    /// CHECK: Sunspot verifier program or VerifyingKey account, validated by address constraint
+    #[account(
+        constraint = proposal.reveal_verifier == Some(zk_verifier.key()) @ PsephosError::InvalidVerifierProgram
+    )]
+    pub zk_verifier: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitRankedBallots<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [b"proposal", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = RankedBallots::ENTRIES_OFFSET,
+        seeds = [b"ranked_ballots", proposal.key().as_ref()],
+        bump
+    )]
+    pub ranked_ballots: Account<'info, RankedBallots>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevealRankedVote<'info> {
+    #[account(mut)]
+    pub revealer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        mut,
+        seeds = [b"vote", proposal.key().as_ref(), vote_record.nullifier.as_ref()],
+        bump = vote_record.bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    #[account(
+        mut,
+        seeds = [b"results", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = results.bump
+    )]
+    pub results: Account<'info, ProposalResults>,
+
+    #[account(
+        mut,
+        seeds = [b"ranked_ballots", proposal.key().as_ref()],
+        bump = ranked_ballots.bump,
+        realloc = RankedBallots::ENTRIES_OFFSET
+            + (ranked_ballots.ballot_count as usize + 1) * ranked::RANKED_BALLOT_SIZE,
+        realloc::payer = revealer,
+        realloc::zero = false
+    )]
+    pub ranked_ballots: Account<'info, RankedBallots>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TallyRound<'info> {
+    #[account(
+        seeds = [b"proposal", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
         bump = proposal.bump
     )]
     pub proposal: Account<'info, Proposal>,
 
     #[account(
-        init,
-        payer = voter,
-        space = 8 + VoteRecord::INIT_SPACE,
-        seeds = [b"vote", proposal.key().as_ref(), nullifier.as_ref()],
-        bump
+        mut,
+        seeds = [b"results", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = results.bump
     )]
-    pub vote_record: Account<'info, VoteRecord>,
+    pub results: Account<'info, ProposalResults>,
 
-    /// Voter's SPL token account for eligibility verification
+    /// May be omitted only if no ballot was revealed; STV surplus
+    /// transfers rewrite ballot transfer values
     #[account(
-        constraint = voter_token_account.mint == proposal.token_mint @ PsephosError::InvalidTokenMint,
-        constraint = voter_token_account.owner == voter.key() @ PsephosError::InvalidTokenOwner,
+        mut,
+        seeds = [b"ranked_ballots", proposal.key().as_ref()],
+        bump = ranked_ballots.bump
     )]
-    pub voter_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub ranked_ballots: Option<Account<'info, RankedBallots>>,
+}
 
-    /// ZK Verifier program for on-chain proof verification
-    /// CHECK: This is the Sunspot verifier program, validated by address constraint
+#[derive(Accounts)]
+pub struct FinalizeProposal<'info> {
     #[account(
-        constraint = zk_verifier.key() == ZK_VERIFIER_PROGRAM_ID @ PsephosError::InvalidVerifierProgram
+        mut,
+        seeds = [b"proposal", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
     )]
-    pub zk_verifier: AccountInfo<'info>,
+    pub proposal: Account<'info, Proposal>,
 
-    pub token_program: Interface<'info, TokenInterface>,
-    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [b"results", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = results.bump
+    )]
+    pub results: Account<'info, ProposalResults>,
+
+    /// Required for encrypted-ballot proposals
+    #[account(
+        seeds = [b"encrypted_tally", proposal.key().as_ref()],
+        bump = encrypted_tally.bump
+    )]
+    pub encrypted_tally: Option<Account<'info, EncryptedTally>>,
+
+    /// Required for proposals with a supply-based quorum
+    #[account(
+        constraint = token_mint.key() == proposal.token_mint @ PsephosError::InvalidTokenMint
+    )]
+    pub token_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// The creator, or anyone once the reveal window has closed; receives
+    /// the finalize bounty
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"deposit", proposal.key().as_ref()],
+        bump = deposit.bump
+    )]
+    pub deposit: Account<'info, ProposalDeposit>,
+
+    /// Receives the refunded deposit
+    #[account(mut, address = proposal.creator @ PsephosError::Unauthorized)]
+    pub creator: SystemAccount<'info>,
+
+    /// Receives the deposit if the proposal misses quorum
+    #[account(
+        mut,
+        seeds = [b"treasury", proposal.governance_space.as_ref()],
+        bump
+    )]
+    pub treasury: SystemAccount<'info>,
 }
 
 #[derive(Accounts)]
-pub struct RevealVote<'info> {
+pub struct FundFinalizeBounty<'info> {
     #[account(mut)]
-    pub revealer: Signer<'info>,
+    pub funder: Signer<'info>,
 
     #[account(
-        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        mut,
+        seeds = [b"proposal", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
         bump = proposal.bump
     )]
     pub proposal: Account<'info, Proposal>,
 
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelProposal<'info> {
+    /// The creator, or a space admin cancelling the proposal as spam
+    pub authority: Signer<'info>,
+
+    /// Receives the closed accounts' rent and, unless slashed, the deposit
+    #[account(mut, address = proposal.creator @ PsephosError::Unauthorized)]
+    pub creator: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"space", governance_space.creator.as_ref(), governance_space.name.as_bytes()],
+        bump = governance_space.bump
+    )]
+    pub governance_space: Account<'info, GovernanceSpace>,
+
     #[account(
         mut,
-        seeds = [b"vote", proposal.key().as_ref(), vote_record.nullifier.as_ref()],
-        bump = vote_record.bump
+        close = creator,
+        seeds = [b"proposal", governance_space.key().as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+        constraint = proposal.creator == authority.key()
+            || governance_space.is_admin(&authority.key()) @ PsephosError::Unauthorized
     )]
-    pub vote_record: Account<'info, VoteRecord>,
+    pub proposal: Account<'info, Proposal>,
 
     #[account(
         mut,
-        seeds = [b"results", proposal.id.to_le_bytes().as_ref()],
+        close = creator,
+        seeds = [b"results", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
         bump = results.bump
     )]
     pub results: Account<'info, ProposalResults>,
+
+    /// CHECK: the encrypted tally's address, required for encrypted-ballot
+    /// proposals and closed if it was set up
+    #[account(mut, seeds = [b"encrypted_tally", proposal.key().as_ref()], bump)]
+    pub encrypted_tally: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: the ranked ballot buffer's address, required for ranked
+    /// proposals and closed if it was set up
+    #[account(mut, seeds = [b"ranked_ballots", proposal.key().as_ref()], bump)]
+    pub ranked_ballots: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mut,
+        close = creator,
+        seeds = [b"deposit", proposal.key().as_ref()],
+        bump = deposit.bump
+    )]
+    pub deposit: Account<'info, ProposalDeposit>,
+
+    /// Receives the deposit of a proposal cancelled as spam
+    #[account(
+        mut,
+        seeds = [b"treasury", governance_space.key().as_ref()],
+        bump
+    )]
+    pub treasury: SystemAccount<'info>,
 }
 
 #[derive(Accounts)]
-pub struct FinalizeProposal<'info> {
+pub struct AmendProposal<'info> {
+    pub creator: Signer<'info>,
+
+    /// Supplies the default voting and reveal periods
+    #[account(
+        seeds = [b"space", governance_space.creator.as_ref(), governance_space.name.as_bytes()],
+        bump = governance_space.bump
+    )]
+    pub governance_space: Account<'info, GovernanceSpace>,
+
     #[account(
         mut,
-        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        seeds = [b"proposal", governance_space.key().as_ref(), proposal.id.to_le_bytes().as_ref()],
         bump = proposal.bump,
-        constraint = proposal.creator == authority.key() @ PsephosError::Unauthorized
+        constraint = proposal.creator == creator.key() @ PsephosError::Unauthorized
     )]
     pub proposal: Account<'info, Proposal>,
 
     #[account(
         mut,
-        seeds = [b"results", proposal.id.to_le_bytes().as_ref()],
+        seeds = [b"results", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
         bump = results.bump
     )]
     pub results: Account<'info, ProposalResults>,
+}
 
-    pub authority: Signer<'info>,
+#[derive(Accounts)]
+#[instruction(option: u8)]
+pub struct InsertTransaction<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+        constraint = proposal.creator == creator.key() @ PsephosError::Unauthorized
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + ProposalTransaction::INIT_SPACE,
+        seeds = [
+            b"transaction",
+            proposal.key().as_ref(),
+            &[option],
+            &[proposal.transaction_counts.get(option as usize).copied().unwrap_or_default()],
+        ],
+        bump
+    )]
+    pub proposal_transaction: Account<'info, ProposalTransaction>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        seeds = [b"results", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = results.bump
+    )]
+    pub results: Account<'info, ProposalResults>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"transaction",
+            proposal.key().as_ref(),
+            &[proposal_transaction.option],
+            &[proposal_transaction.index],
+        ],
+        bump = proposal_transaction.bump,
+        close = creator
+    )]
+    pub proposal_transaction: Account<'info, ProposalTransaction>,
+
+    /// Receives the executed transaction's rent
+    #[account(mut, address = proposal.creator @ PsephosError::Unauthorized)]
+    pub creator: SystemAccount<'info>,
+
+    /// Signs the instruction
+    #[account(
+        mut,
+        seeds = [b"treasury", proposal.governance_space.as_ref()],
+        bump
+    )]
+    pub treasury: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseVoteRecord<'info> {
+    /// The vote record's refund key
+    pub refund_authority: Signer<'info>,
+
+    /// CHECK: any account chosen by the refund authority to receive the rent
+    #[account(mut)]
+    pub recipient: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        close = recipient,
+        seeds = [b"vote", vote_record.proposal.as_ref(), vote_record.nullifier.as_ref()],
+        bump = vote_record.bump,
+        constraint = vote_record.refund_key == refund_authority.key() @ PsephosError::Unauthorized
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    /// CHECK: the record's proposal; read only while it still exists, as its
+    /// status gates the close
+    #[account(address = vote_record.proposal)]
+    pub proposal: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ArchiveProposal<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        close = creator,
+        seeds = [b"proposal", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+        constraint = proposal.creator == creator.key() @ PsephosError::Unauthorized
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        mut,
+        close = creator,
+        seeds = [b"results", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = results.bump
+    )]
+    pub results: Account<'info, ProposalResults>,
+
+    #[account(
+        mut,
+        close = creator,
+        seeds = [b"deposit", proposal.key().as_ref()],
+        bump = deposit.bump
+    )]
+    pub deposit: Account<'info, ProposalDeposit>,
+
+    /// CHECK: the encrypted tally's address, required for encrypted-ballot
+    /// proposals and closed if it was set up
+    #[account(mut, seeds = [b"encrypted_tally", proposal.key().as_ref()], bump)]
+    pub encrypted_tally: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: the ranked ballot buffer's address, required for ranked
+    /// proposals and closed if it was set up
+    #[account(mut, seeds = [b"ranked_ballots", proposal.key().as_ref()], bump)]
+    pub ranked_ballots: Option<UncheckedAccount<'info>>,
+
+    /// Closed as well for proposals that set one up, returning unspent fees
+    #[account(
+        mut,
+        close = creator,
+        seeds = [b"relayer_pool", proposal.key().as_ref()],
+        bump = relayer_pool.bump
+    )]
+    pub relayer_pool: Option<Account<'info, RelayerPool>>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + ProposalArchive::INIT_SPACE,
+        seeds = [b"archive", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub archive: Account<'info, ProposalArchive>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ViewProposal<'info> {
+    #[account(
+        seeds = [b"proposal", proposal.governance_space.as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct SpaceCreated {
+    pub governance_space: Pubkey,
+    pub creator: Pubkey,
+    pub token_mint: Pubkey,
+}
+
+#[event]
+pub struct SpaceUpdated {
+    pub governance_space: Pubkey,
+    pub admin: Pubkey,
+    pub token_mint: Pubkey,
+}
+
+#[event]
+pub struct TreasuryDeposit {
+    pub governance_space: Pubkey,
+    pub depositor: Pubkey,
+    /// `None` for SOL
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ProposalCreated {
+    pub proposal: Pubkey,
+    pub governance_space: Pubkey,
+    pub proposal_id: u64,
+    pub creator: Pubkey,
+    pub token_mint: Pubkey,
+    pub zk_verifier: Pubkey,
+    pub reveal_verifier: Option<Pubkey>,
+    pub ballot_mode: BallotMode,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub reveal_end_time: i64,
+    pub num_options: u8,
+}
+
+#[event]
+pub struct VoteCast {
+    pub proposal: Pubkey,
+    pub nullifier: [u8; 32],
+    /// Vote commitment, or the ballot hash for encrypted ballots
+    pub vote_commitment: [u8; 32],
+    pub timestamp: i64,
+    /// Number of votes cast including this one
+    pub vote_count: u64,
+    pub weight: u64,
+}
+
+#[event]
+pub struct VoteRevealed {
+    pub proposal: Pubkey,
+    pub nullifier: [u8; 32],
+    /// Chosen option, or the approval bitmask
+    pub vote_choice: u16,
+    pub weight: u64,
+}
+
+#[event]
+pub struct RankedVoteRevealed {
+    pub proposal: Pubkey,
+    pub nullifier: [u8; 32],
+    pub ranking: Vec<u8>,
+    pub weight: u64,
+}
+
+#[event]
+pub struct TallyRoundCompleted {
+    pub proposal: Pubkey,
+    /// 1-based round number
+    pub round: u8,
+    pub tallies: Vec<u64>,
+    pub exhausted_weight: u64,
+    pub eliminated: Option<u8>,
+    /// Options elected after the round (STV)
+    pub elected: Vec<u8>,
+    /// Whether this was the final round
+    pub complete: bool,
+}
+
+#[event]
+pub struct ProposalFinalized {
+    pub proposal: Pubkey,
+    pub proposal_id: u64,
+    pub vote_count: u64,
+    pub tallies: Vec<u64>,
+    pub outcome: ProposalOutcome,
+    pub winning_option: Option<u8>,
+    pub finalizer: Pubkey,
+    /// Lamports paid to the finalizer
+    pub bounty: u64,
+}
+
+#[event]
+pub struct ProposalCancelled {
+    pub proposal: Pubkey,
+    pub proposal_id: u64,
+    pub cancelled_by: Pubkey,
+    pub deposit_slashed: bool,
+}
+
+#[event]
+pub struct DepositSettled {
+    pub proposal: Pubkey,
+    pub amount: u64,
+    pub recipient: Pubkey,
+    pub slashed: bool,
+}
+
+#[event]
+pub struct ProposalAmended {
+    pub proposal: Pubkey,
+    pub proposal_id: u64,
+    pub num_options: u8,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub reveal_end_time: i64,
+}
+
+#[event]
+pub struct TransactionInserted {
+    pub proposal: Pubkey,
+    pub option: u8,
+    pub index: u8,
+    pub program_id: Pubkey,
+}
+
+#[event]
+pub struct TransactionExecuted {
+    pub proposal: Pubkey,
+    pub option: u8,
+    pub index: u8,
+    pub program_id: Pubkey,
+}
+
+#[event]
+pub struct ProposalArchived {
+    pub proposal: Pubkey,
+    pub archive: Pubkey,
+    pub proposal_id: u64,
+    pub outcome: ProposalOutcome,
+}
+
+#[event]
+pub struct RelayerPaid {
+    pub proposal: Pubkey,
+    pub relayer: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct EncryptedTallyInitialized {
+    pub proposal: Pubkey,
+    pub election_key: [u8; 64],
+    pub num_trustees: u8,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct DecryptionShareSubmitted {
+    pub proposal: Pubkey,
+    pub trustee_index: u8,
+    pub share: [u8; 64],
+}
+
+#[event]
+pub struct TallyDecrypted {
+    pub proposal: Pubkey,
+    pub tallies: Vec<u64>,
+}
+
+#[event]
+pub struct VerifierAdded {
+    pub program_id: Pubkey,
+    pub circuit_version: u32,
+    pub layout: PublicInputLayout,
+}
+
+#[event]
+pub struct VerifierDeactivated {
+    pub program_id: Pubkey,
 }
 
 // ============================================================================
@@ -518,14 +3861,14 @@ pub enum PsephosError {
     AlreadyRevealed,
     #[msg("Unauthorized to perform this action")]
     Unauthorized,
-    #[msg("Invalid reveal - voter secret does not match nullifier")]
+    #[msg("Invalid reveal - choice and secret do not match vote commitment")]
     InvalidReveal,
     #[msg("Invalid public witness - size or format incorrect")]
     InvalidPublicWitness,
     #[msg("Public input threshold does not match proposal threshold")]
     ThresholdMismatch,
-    #[msg("Public input proposal ID does not match")]
-    ProposalIdMismatch,
+    #[msg("Public input proposal domain does not match the proposal")]
+    ProposalDomainMismatch,
     #[msg("Public input commitment does not match submitted commitment")]
     CommitmentMismatch,
     #[msg("Public input nullifier does not match submitted nullifier")]
@@ -538,4 +3881,132 @@ pub enum PsephosError {
     InvalidTokenOwner,
     #[msg("Invalid ZK verifier program")]
     InvalidVerifierProgram,
+    #[msg("Voter token account is required for proposals without an eligibility root")]
+    MissingTokenAccount,
+    #[msg("Public input eligibility root does not match proposal snapshot root")]
+    EligibilityRootMismatch,
+    #[msg("Relayed votes require a proposal with an eligibility snapshot root")]
+    RelayerRequiresSnapshot,
+    #[msg("Verifier is not registered or has been deactivated")]
+    VerifierNotRegistered,
+    #[msg("Verifier is already registered")]
+    VerifierAlreadyRegistered,
+    #[msg("Verifier registry is full")]
+    VerifierRegistryFull,
+    #[msg("Verifier public-input layout does not match proposal eligibility mode")]
+    VerifierLayoutMismatch,
+    #[msg("Verifying key is malformed")]
+    InvalidVerifyingKey,
+    #[msg("Groth16 proof verification failed")]
+    ProofVerificationFailed,
+    #[msg("Proof commitments do not match the verifying key")]
+    UnsupportedProofCommitment,
+    #[msg("Public witness header is malformed or has the wrong input count")]
+    WitnessHeaderInvalid,
+    #[msg("Public witness length does not match its header")]
+    WitnessLengthInvalid,
+    #[msg("Public witness element is not a canonical BN254 field element")]
+    NonCanonicalFieldElement,
+    #[msg("Public witness integer input has nonzero high bytes")]
+    WitnessValueOutOfRange,
+    #[msg("Instruction does not match the proposal's ballot mode")]
+    BallotModeMismatch,
+    #[msg("Encrypted ballots do not support eligibility snapshots")]
+    EncryptedSnapshotUnsupported,
+    #[msg("Votes have already been cast on this proposal")]
+    VotingStarted,
+    #[msg("Trustee set or threshold is invalid")]
+    InvalidTrusteeSet,
+    #[msg("Trustee index is out of range")]
+    InvalidTrusteeIndex,
+    #[msg("Ciphertext or election key is not a valid curve point")]
+    InvalidCiphertext,
+    #[msg("Encrypted tally cannot hold more votes")]
+    EncryptedTallyFull,
+    #[msg("Decryption share or its proof is invalid")]
+    InvalidDecryptionShare,
+    #[msg("Trustee has already submitted a decryption share")]
+    DecryptionShareAlreadySubmitted,
+    #[msg("A participating trustee has not submitted a decryption share")]
+    MissingDecryptionShare,
+    #[msg("Claimed tallies do not match the decrypted aggregate")]
+    TallyDecryptionFailed,
+    #[msg("Encrypted tally has already been decrypted")]
+    TallyAlreadyDecrypted,
+    #[msg("Encrypted tally must be decrypted before finalizing")]
+    TallyNotDecrypted,
+    #[msg("Proposal has been cancelled")]
+    ProposalCancelled,
+    #[msg("Proposal status does not allow this action")]
+    InvalidStatusTransition,
+    #[msg("Reveal period must be positive")]
+    InvalidRevealPeriod,
+    #[msg("Voting period is outside the allowed bounds")]
+    InvalidVotingPeriod,
+    #[msg("Voting start time is in the past")]
+    StartTimeInPast,
+    #[msg("Reveal window has closed")]
+    RevealWindowClosed,
+    #[msg("Reveal window is still open and not every vote has been revealed")]
+    RevealWindowOpen,
+    #[msg("Quorum must be a positive vote count or 1-10000 basis points of supply")]
+    InvalidQuorum,
+    #[msg("Supermajority threshold must be 1-10000 basis points")]
+    InvalidApprovalThreshold,
+    #[msg("Token mint account is required for a supply-based quorum")]
+    MissingTokenMint,
+    #[msg("Space name must be 1 to 32 bytes")]
+    InvalidSpaceName,
+    #[msg("Space needs 1 to 5 distinct admins")]
+    InvalidSpaceAdmins,
+    #[msg("Proposer allowlist is too long or lists an account twice")]
+    InvalidProposerList,
+    #[msg("Creator is not allowlisted and does not hold the minimum token balance")]
+    ProposerNotEligible,
+    #[msg("Proposal deposit must be zero or at least the rent-exempt minimum")]
+    InvalidProposalDeposit,
+    #[msg("Proposal must be finalized first")]
+    ProposalNotSettled,
+    #[msg("Execution delay cannot be negative")]
+    InvalidExecutionDelay,
+    #[msg("Attached instruction is too large, targets psephos, or the option is full")]
+    InvalidProposalTransaction,
+    #[msg("Options with attached transactions cannot be removed")]
+    ProposalHasTransactions,
+    #[msg("Proposal did not pass with this transaction's option")]
+    ProposalNotPassed,
+    #[msg("Execution timelock has not elapsed")]
+    TimelockNotElapsed,
+    #[msg("Transactions must be executed in order")]
+    TransactionOutOfOrder,
+    #[msg("Proposal has transactions left to execute")]
+    ExecutionPending,
+    #[msg("Token weighting needs a positive unit and cap")]
+    InvalidVoteWeighting,
+    #[msg("Token weighting is not supported for encrypted ballots")]
+    WeightingUnsupported,
+    #[msg("Public input weight bounds do not match the proposal")]
+    WeightingMismatch,
+    #[msg("Voting method is not supported with this ballot mode, weighting or quorum")]
+    InvalidVotingMethod,
+    #[msg("Public input option count does not match the proposal")]
+    OptionCountMismatch,
+    #[msg("Instruction does not match the proposal's voting method")]
+    VotingMethodMismatch,
+    #[msg("Ranking must list distinct options of the proposal")]
+    InvalidRanking,
+    #[msg("Ranked tally has rounds left to count")]
+    RankedTallyIncomplete,
+    #[msg("Ranked tally is already complete")]
+    RankedTallyComplete,
+    #[msg("Ranked ballot buffer is required for this ranked-choice proposal")]
+    MissingRankedBallots,
+    #[msg("Supply-based quorum needs token-weighted votes")]
+    QuorumNeedsTokenWeighting,
+    #[msg("Encrypted tally account is required for an encrypted-ballot proposal")]
+    MissingEncryptedTally,
+    #[msg("Token weighting needs an eligibility snapshot")]
+    WeightingNeedsSnapshot,
+    #[msg("Every unexecuted attached transaction must be passed")]
+    MissingProposalTransactions,
 }
@@ -0,0 +1,682 @@
+//! Ranked ballots, instant-runoff and single-transferable-vote rounds
+//!
+//! A ranking is packed into a u64 as 4-bit slots, first preference in the
+//! lowest slot, each holding `option + 1`; a zero slot ends the ranking. The
+//! packed value is what the vote commitment opens to.
+//!
+//! Revealed ballots are appended to a `RankedBallots` buffer as fixed-size
+//! entries after the account's header, so a crank can count a slice of them
+//! without deserializing the whole buffer:
+//!
+//! ```text
+//! weight: u64 LE || ranking: u64 LE || transfer_value: u64 LE
+//! ```
+//!
+//! Each round counts every ballot for its highest-ranked option still in
+//! the race, at `weight * transfer_value` in fixed-point units of
+//! `1 / SCALE`. Instant runoff never changes a transfer value: an option
+//! with a majority of the counted weight wins, otherwise the last-placed
+//! option is eliminated and the ballots are counted again.
+//!
+//! STV elects against the Droop quota of the first round's total. A round
+//! elects its leading option if it reaches the quota, otherwise eliminates
+//! the last-placed one. An elected option's surplus passes on by scaling
+//! the transfer value of every ballot counted for it by
+//! `surplus / tally` while the next round is counted, so those ballots
+//! carry only the surplus to their next preference. Once no more options
+//! remain in the race than seats left, they are all elected, unless no
+//! ballot counts for any of them: then the tally ends with the seats left
+//! empty.
+
+use crate::tally::VotingMethod;
+use crate::{ProposalResults, RankedRound, SurplusTransfer, MAX_OPTIONS};
+
+/// Bits per ranking slot
+pub const RANK_BITS: u32 = 4;
+/// Bytes per ballot entry in the buffer
+pub const RANKED_BALLOT_SIZE: usize = 24;
+/// Fixed-point scale of transfer values and round tallies
+pub const SCALE: u64 = 1_000_000_000;
+
+/// Pack a ranking of distinct options below `num_options`, or `None` if it
+/// is empty, too long, out of range or repeats an option
+pub fn pack_ranking(ranking: &[u8], num_options: usize) -> Option<u64> {
+    if ranking.is_empty() || ranking.len() > num_options || num_options > MAX_OPTIONS {
+        return None;
+    }
+    let mut seen = 0u16;
+    let mut packed = 0u64;
+    for (rank, &option) in ranking.iter().enumerate() {
+        if option as usize >= num_options || seen & (1 << option) != 0 {
+            return None;
+        }
+        seen |= 1 << option;
+        packed |= (option as u64 + 1) << (RANK_BITS * rank as u32);
+    }
+    Some(packed)
+}
+
+/// Options of a packed ranking in order of preference
+pub fn preferences(packed: u64) -> impl Iterator<Item = u8> {
+    (0..MAX_OPTIONS as u32)
+        .map(move |rank| ((packed >> (RANK_BITS * rank)) & 0xf) as u8)
+        .take_while(|slot| *slot != 0)
+        .map(|slot| slot - 1)
+}
+
+/// Highest-ranked option not in the `excluded` bitmask
+pub fn first_continuing(packed: u64, excluded: u16) -> Option<u8> {
+    preferences(packed).find(|option| excluded & (1 << option) == 0)
+}
+
+/// A ballot buffer entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RankedBallot {
+    pub weight: u64,
+    pub ranking: u64,
+    /// Share of the weight still to count, out of `SCALE`
+    pub transfer_value: u64,
+}
+
+impl RankedBallot {
+    /// A freshly revealed ballot at its full weight
+    pub fn new(weight: u64, ranking: u64) -> Self {
+        Self { weight, ranking, transfer_value: SCALE }
+    }
+
+    /// Weight counted for the ballot, in units of `1 / SCALE`
+    pub fn value(&self) -> u128 {
+        self.weight as u128 * self.transfer_value as u128
+    }
+}
+
+/// Read the entry at `index` of a ballot buffer
+pub fn read_ballot(entries: &[u8], index: usize) -> RankedBallot {
+    let entry = &entries[index * RANKED_BALLOT_SIZE..(index + 1) * RANKED_BALLOT_SIZE];
+    let field = |i: usize| u64::from_le_bytes(entry[i * 8..(i + 1) * 8].try_into().unwrap());
+    RankedBallot { weight: field(0), ranking: field(1), transfer_value: field(2) }
+}
+
+/// Write the entry at `index` of a ballot buffer
+pub fn write_ballot(entries: &mut [u8], index: usize, ballot: &RankedBallot) {
+    let entry = &mut entries[index * RANKED_BALLOT_SIZE..(index + 1) * RANKED_BALLOT_SIZE];
+    entry[..8].copy_from_slice(&ballot.weight.to_le_bytes());
+    entry[8..16].copy_from_slice(&ballot.ranking.to_le_bytes());
+    entry[16..].copy_from_slice(&ballot.transfer_value.to_le_bytes());
+}
+
+/// Scale a transfer value by a `ratio` out of `SCALE`
+pub fn scale_transfer_value(transfer_value: u64, ratio: u64) -> u64 {
+    (transfer_value as u128 * ratio as u128 / SCALE as u128) as u64
+}
+
+/// Droop quota for `seats` seats over `total` counted weight: the least
+/// weight that no more than `seats` options can all reach
+pub fn droop_quota(total: u128, seats: u8) -> u128 {
+    total / (seats as u128 + 1) + 1
+}
+
+/// What a completed round decides
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundDecision {
+    /// The option holds a majority of the counted weight
+    Winner(u8),
+    /// STV: the option reached the quota; ballots counted for it pass on at
+    /// `transfer_ratio / SCALE` of their value
+    Elect { option: u8, transfer_ratio: u64 },
+    /// STV: no more options remain than seats left; elect them all
+    ElectRemaining,
+    /// No majority or quota reached; the option placed last
+    Eliminate(u8),
+    /// No ballot counted for any remaining option
+    NoWinner,
+}
+
+/// Options not in the `excluded` bitmask with their tallies
+fn continuing(tallies: &[u128], excluded: u16) -> impl Iterator<Item = (u8, u128)> + '_ {
+    tallies
+        .iter()
+        .enumerate()
+        .filter(move |(option, _)| excluded & (1 << option) == 0)
+        .map(|(option, tally)| (option as u8, *tally))
+}
+
+/// Last-placed continuing option; ties eliminate the highest-indexed one
+fn last_place(tallies: &[u128], excluded: u16) -> Option<u8> {
+    continuing(tallies, excluded)
+        .min_by(|(a, tally_a), (b, tally_b)| tally_a.cmp(tally_b).then(b.cmp(a)))
+        .map(|(option, _)| option)
+}
+
+/// Continuing options from highest to lowest tally; ties put the
+/// lowest-indexed option first
+pub fn standings(tallies: &[u128], excluded: u16) -> Vec<u8> {
+    let mut options: Vec<(u8, u128)> = continuing(tallies, excluded).collect();
+    options.sort_by(|(a, tally_a), (b, tally_b)| tally_b.cmp(tally_a).then(a.cmp(b)));
+    options.into_iter().map(|(option, _)| option).collect()
+}
+
+/// Decide an instant-runoff round from its tallies; `eliminated` is the
+/// bitmask of options out of the race
+pub fn irv_decision(tallies: &[u128], eliminated: u16) -> RoundDecision {
+    let total: u128 = continuing(tallies, eliminated).map(|(_, tally)| tally).sum();
+    if total == 0 {
+        return RoundDecision::NoWinner;
+    }
+
+    // A lone remaining option holds all the counted weight
+    if let Some((option, _)) = continuing(tallies, eliminated).find(|(_, tally)| tally * 2 > total) {
+        return RoundDecision::Winner(option);
+    }
+
+    let last = last_place(tallies, eliminated).expect("total > 0 implies a continuing option");
+    RoundDecision::Eliminate(last)
+}
+
+/// Decide an STV round from its tallies; `excluded` is the bitmask of
+/// options elected or eliminated so far
+///
+/// At most one option is elected per round, the leader, ties going to the
+/// lowest index, so its surplus is transferred before the next is checked.
+/// A round with no weight left for any continuing option ends the tally
+/// with its remaining seats unfilled.
+pub fn stv_decision(tallies: &[u128], excluded: u16, seats_left: usize, quota: u128) -> RoundDecision {
+    if continuing(tallies, excluded).all(|(_, tally)| tally == 0) {
+        return RoundDecision::NoWinner;
+    }
+
+    let order = standings(tallies, excluded);
+    if order.len() <= seats_left {
+        return RoundDecision::ElectRemaining;
+    }
+
+    let leader = order[0];
+    let tally = tallies[leader as usize];
+    if tally >= quota {
+        let transfer_ratio = (tally - quota) * SCALE as u128 / tally;
+        return RoundDecision::Elect { option: leader, transfer_ratio: transfer_ratio as u64 };
+    }
+
+    let last = last_place(tallies, excluded).expect("more options than seats left");
+    RoundDecision::Eliminate(last)
+}
+
+/// A round closed by `count_slice`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClosedRound {
+    pub decision: RoundDecision,
+    /// Options the round elected (STV)
+    pub elected: Vec<u8>,
+}
+
+/// Count the next slice of at most `max_ballots` of the first `ballot_count`
+/// ballot buffer entries toward the current round, resuming at
+/// `results.tally_cursor`
+///
+/// Ballots counted for last round's elected option have their transfer
+/// value scaled in `entries` as they are counted. Once the round's last
+/// ballot is counted the round is decided, recorded in `results.rounds` and,
+/// unless that completes the tally, the next round is set up to start from
+/// the first ballot. Returns the closed round, or `None` while ballots of
+/// the current one are left.
+pub fn count_slice(
+    results: &mut ProposalResults,
+    method: VotingMethod,
+    entries: &mut [u8],
+    ballot_count: u32,
+    max_ballots: u32,
+) -> Option<ClosedRound> {
+    let excluded = results.excluded();
+    let start = results.tally_cursor;
+    let end = start.saturating_add(max_ballots).min(ballot_count);
+    for index in start as usize..end as usize {
+        let mut ballot = read_ballot(entries, index);
+        // Ballots counted for last round's elected option carry on at the
+        // surplus share of their value
+        if let Some(transfer) = results.transfer {
+            let before = excluded & !(1 << transfer.option);
+            if first_continuing(ballot.ranking, before) == Some(transfer.option) {
+                ballot.transfer_value = scale_transfer_value(ballot.transfer_value, transfer.ratio);
+                write_ballot(entries, index, &ballot);
+            }
+        }
+        match first_continuing(ballot.ranking, excluded) {
+            Some(option) => results.round_tallies[option as usize] += ballot.value(),
+            None => results.round_exhausted += ballot.value(),
+        }
+    }
+    results.tally_cursor = end;
+    if end < ballot_count {
+        return None;
+    }
+
+    let decision = match method {
+        VotingMethod::SingleTransferableVote { seats } => {
+            if results.rounds.is_empty() {
+                results.quota = droop_quota(results.round_tallies.iter().sum(), seats);
+            }
+            let seats_left = seats as usize - results.elected.len();
+            stv_decision(&results.round_tallies, excluded, seats_left, results.quota)
+        }
+        _ => irv_decision(&results.round_tallies, results.eliminated),
+    };
+
+    // The final round's tallies stay in `tallies` for finalize
+    let whole = |value: u128| (value / SCALE as u128) as u64;
+    let tallies: Vec<u64> = results.round_tallies.iter().map(|tally| whole(*tally)).collect();
+    results.tallies = tallies.clone();
+
+    let newly_elected = match decision {
+        RoundDecision::Elect { option, .. } => vec![option],
+        RoundDecision::ElectRemaining => standings(&results.round_tallies, excluded),
+        _ => Vec::new(),
+    };
+    let (eliminated, elected) = match decision {
+        RoundDecision::Eliminate(option) => (Some(option), None),
+        RoundDecision::Elect { option, .. } => (None, Some(option)),
+        _ => (None, None),
+    };
+    results.rounds.push(RankedRound {
+        tallies,
+        exhausted_weight: whole(results.round_exhausted),
+        eliminated,
+        elected,
+    });
+    results.elected.extend_from_slice(&newly_elected);
+    if let Some(option) = eliminated {
+        results.eliminated |= 1 << option;
+    }
+    results.transfer = match decision {
+        RoundDecision::Elect { option, transfer_ratio } => Some(SurplusTransfer { option, ratio: transfer_ratio }),
+        _ => None,
+    };
+
+    let seats_filled = match method {
+        VotingMethod::SingleTransferableVote { seats } => results.elected.len() >= seats as usize,
+        _ => false,
+    };
+    results.tally_complete = seats_filled
+        || matches!(
+            decision,
+            RoundDecision::Winner(_) | RoundDecision::ElectRemaining | RoundDecision::NoWinner
+        );
+    if !results.tally_complete {
+        results.round_tallies.iter_mut().for_each(|tally| *tally = 0);
+        results.round_exhausted = 0;
+        results.tally_cursor = 0;
+    }
+
+    Some(ClosedRound { decision, elected: newly_elected })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rankings_round_trip() {
+        let packed = pack_ranking(&[2, 0, 9], 10).unwrap();
+        assert_eq!(packed, 0xa13);
+        assert_eq!(preferences(packed).collect::<Vec<_>>(), vec![2, 0, 9]);
+        assert_eq!(first_continuing(packed, 1 << 2), Some(0));
+        assert_eq!(first_continuing(packed, 0b10_0000_0101), None);
+
+        assert_eq!(pack_ranking(&[], 3), None);
+        assert_eq!(pack_ranking(&[0, 0], 3), None);
+        assert_eq!(pack_ranking(&[3], 3), None);
+    }
+
+    #[test]
+    fn ballot_entries_round_trip() {
+        let mut entries = vec![0u8; 2 * RANKED_BALLOT_SIZE];
+        let ballot = RankedBallot::new(7, 0x21);
+        write_ballot(&mut entries, 1, &ballot);
+        assert_eq!(read_ballot(&entries, 0), RankedBallot { weight: 0, ranking: 0, transfer_value: 0 });
+        assert_eq!(read_ballot(&entries, 1), ballot);
+        assert_eq!(ballot.value(), 7 * SCALE as u128);
+    }
+
+    #[test]
+    fn majority_wins_outright() {
+        assert_eq!(irv_decision(&[6, 3, 2], 0), RoundDecision::Winner(0));
+        // Eliminated options' tallies are ignored
+        assert_eq!(irv_decision(&[4, 4, 9], 0b100), RoundDecision::Eliminate(1));
+    }
+
+    #[test]
+    fn last_place_is_eliminated_until_one_remains() {
+        assert_eq!(irv_decision(&[4, 3, 2], 0), RoundDecision::Eliminate(2));
+        assert_eq!(irv_decision(&[5, 5, 0], 0b100), RoundDecision::Eliminate(1));
+        assert_eq!(irv_decision(&[0, 7, 0], 0b101), RoundDecision::Winner(1));
+        assert_eq!(irv_decision(&[0, 0], 0), RoundDecision::NoWinner);
+    }
+
+    #[test]
+    fn full_runoff() {
+        // Ballots as (weight, ranking)
+        let ballots = [(4, vec![0, 1]), (3, vec![1, 2]), (2, vec![2, 1])];
+        let mut eliminated = 0u16;
+        let winner = loop {
+            let mut tallies = [0u128; 3];
+            for (weight, ranking) in &ballots {
+                let packed = pack_ranking(ranking, 3).unwrap();
+                if let Some(option) = first_continuing(packed, eliminated) {
+                    tallies[option as usize] += *weight as u128;
+                }
+            }
+            match irv_decision(&tallies, eliminated) {
+                RoundDecision::Winner(option) => break Some(option),
+                RoundDecision::Eliminate(option) => eliminated |= 1 << option,
+                RoundDecision::NoWinner => break None,
+                decision => panic!("unexpected {decision:?}"),
+            }
+        };
+        // Option 2 is eliminated and its ballots carry option 1 past option 0
+        assert_eq!(winner, Some(1));
+    }
+
+    /// Results of a proposal with `num_options` options before its first
+    /// tally round
+    fn fresh_results(num_options: usize) -> ProposalResults {
+        ProposalResults {
+            proposal: Default::default(),
+            tallies: vec![0; num_options],
+            counted_weight: 0,
+            outcome: None,
+            winning_option: None,
+            eliminated: 0,
+            elected: Vec::new(),
+            tally_cursor: 0,
+            round_tallies: vec![0; num_options],
+            round_exhausted: 0,
+            quota: 0,
+            transfer: None,
+            rounds: Vec::new(),
+            tally_complete: false,
+            bump: 255,
+        }
+    }
+
+    /// Ballot buffer entries of (weight, ranking) ballots
+    fn entries(ballots: &[(u64, Vec<u8>)], num_options: usize) -> Vec<u8> {
+        let mut entries = vec![0u8; ballots.len() * RANKED_BALLOT_SIZE];
+        for (index, (weight, ranking)) in ballots.iter().enumerate() {
+            let packed = pack_ranking(ranking, num_options).unwrap();
+            write_ballot(&mut entries, index, &RankedBallot::new(*weight, packed));
+        }
+        entries
+    }
+
+    #[test]
+    fn slices_resume_at_the_cursor() {
+        let ballots = [(4, vec![0, 1]), (3, vec![1, 2]), (2, vec![2, 1])];
+        let mut buffer = entries(&ballots, 3);
+        let mut results = fresh_results(3);
+        let scale = SCALE as u128;
+
+        assert_eq!(count_slice(&mut results, VotingMethod::RankedChoice, &mut buffer, 3, 2), None);
+        assert_eq!(results.tally_cursor, 2);
+        assert_eq!(results.round_tallies, vec![4 * scale, 3 * scale, 0]);
+        assert!(results.rounds.is_empty());
+
+        let closed = count_slice(&mut results, VotingMethod::RankedChoice, &mut buffer, 3, 2).unwrap();
+        assert_eq!(closed, ClosedRound { decision: RoundDecision::Eliminate(2), elected: Vec::new() });
+        assert_eq!(
+            results.rounds,
+            vec![RankedRound { tallies: vec![4, 3, 2], exhausted_weight: 0, eliminated: Some(2), elected: None }]
+        );
+        assert_eq!(results.tallies, vec![4, 3, 2]);
+        assert_eq!(results.eliminated, 0b100);
+        assert!(!results.tally_complete);
+
+        // The next round starts over from the first ballot
+        assert_eq!(results.tally_cursor, 0);
+        assert_eq!(results.round_tallies, vec![0; 3]);
+        assert_eq!(results.round_exhausted, 0);
+    }
+
+    #[test]
+    fn rounds_advance_one_ballot_per_slice_to_a_winner() {
+        let ballots = [(4, vec![0, 1]), (3, vec![1, 2]), (2, vec![2, 1])];
+        let mut buffer = entries(&ballots, 3);
+        let mut results = fresh_results(3);
+
+        let mut slices = 0;
+        let mut decisions = Vec::new();
+        while !results.tally_complete {
+            slices += 1;
+            if let Some(closed) = count_slice(&mut results, VotingMethod::RankedChoice, &mut buffer, 3, 1) {
+                decisions.push(closed.decision);
+            }
+        }
+        // Option 2's ballot carries option 1 past option 0
+        assert_eq!(slices, 6);
+        assert_eq!(decisions, vec![RoundDecision::Eliminate(2), RoundDecision::Winner(1)]);
+        assert_eq!(results.rounds[1].tallies, vec![4, 5, 0]);
+        assert_eq!(results.tallies, vec![4, 5, 0]);
+        // Counting stops with the cursor past the last ballot
+        assert_eq!(results.tally_cursor, 3);
+        // Instant runoff leaves transfer values alone
+        assert_eq!(read_ballot(&buffer, 2), RankedBallot::new(2, pack_ranking(&[2, 1], 3).unwrap()));
+    }
+
+    #[test]
+    fn elimination_ties_drop_the_highest_option() {
+        let ballots = [(2, vec![0]), (1, vec![1, 0]), (1, vec![2])];
+        let mut buffer = entries(&ballots, 3);
+        let mut results = fresh_results(3);
+
+        // Options 1 and 2 tie for last place
+        let closed = count_slice(&mut results, VotingMethod::RankedChoice, &mut buffer, 3, 10).unwrap();
+        assert_eq!(closed.decision, RoundDecision::Eliminate(2));
+
+        // Option 2's only ballot is exhausted, leaving option 0 a majority
+        let closed = count_slice(&mut results, VotingMethod::RankedChoice, &mut buffer, 3, 10).unwrap();
+        assert_eq!(closed.decision, RoundDecision::Winner(0));
+        assert_eq!(
+            results.rounds[1],
+            RankedRound { tallies: vec![2, 1, 0], exhausted_weight: 1, eliminated: None, elected: None }
+        );
+        assert!(results.tally_complete);
+    }
+
+    #[test]
+    fn an_empty_buffer_has_no_winner() {
+        let mut results = fresh_results(2);
+        let closed = count_slice(&mut results, VotingMethod::RankedChoice, &mut [], 0, 10).unwrap();
+        assert_eq!(closed.decision, RoundDecision::NoWinner);
+        assert!(results.tally_complete);
+        assert_eq!(results.rounds.len(), 1);
+    }
+
+    #[test]
+    fn stv_elects_no_one_without_votes() {
+        let method = VotingMethod::SingleTransferableVote { seats: 2 };
+        let mut results = fresh_results(3);
+        let closed = count_slice(&mut results, method, &mut [], 0, 10).unwrap();
+        assert_eq!(closed, ClosedRound { decision: RoundDecision::NoWinner, elected: Vec::new() });
+        assert!(results.tally_complete);
+        assert!(results.elected.is_empty());
+
+        // Once the first seat's ballots are exhausted the second stays empty
+        let ballots = [(3, vec![0]), (1, vec![0])];
+        let mut buffer = entries(&ballots, 3);
+        let mut results = fresh_results(3);
+        while !results.tally_complete {
+            count_slice(&mut results, method, &mut buffer, 2, 10);
+        }
+        assert_eq!(results.elected, vec![0]);
+        // Option 0's surplus of 4 - 4/3 has nowhere to go
+        assert_eq!(results.rounds.len(), 2);
+        assert_eq!(results.rounds[1].exhausted_weight, 2);
+        assert_eq!(stv_decision(&[4, 0, 0], 0b1, 1, 2), RoundDecision::NoWinner);
+    }
+
+    /// Run `count_slice` three ballots at a time until the STV tally
+    /// completes, returning the elected options in order
+    fn run_stv(ballots: &[(u64, Vec<u8>)], num_options: usize, seats: u8) -> Vec<u8> {
+        let mut buffer = entries(ballots, num_options);
+        let mut results = fresh_results(num_options);
+        let method = VotingMethod::SingleTransferableVote { seats };
+        while !results.tally_complete {
+            count_slice(&mut results, method, &mut buffer, ballots.len() as u32, 3);
+        }
+        results.elected
+    }
+
+    #[test]
+    fn droop_quota_and_transfer_values() {
+        let scale = SCALE as u128;
+        assert_eq!(droop_quota(10 * scale, 2), 10 * scale / 3 + 1);
+        assert_eq!(droop_quota(0, 1), 1);
+        assert_eq!(scale_transfer_value(SCALE, SCALE / 4), SCALE / 4);
+        assert_eq!(scale_transfer_value(SCALE / 2, SCALE / 2), SCALE / 4);
+        assert!(VotingMethod::SingleTransferableVote { seats: 2 }.is_valid(3));
+        assert!(!VotingMethod::SingleTransferableVote { seats: 3 }.is_valid(3));
+        assert!(!VotingMethod::SingleTransferableVote { seats: 0 }.is_valid(3));
+    }
+
+    #[test]
+    fn stv_elects_the_leader_over_quota_with_its_surplus_ratio() {
+        let scale = SCALE as u128;
+        let quota = droop_quota(9 * scale, 2);
+        let decision = stv_decision(&[6 * scale, 2 * scale, scale], 0, 2, quota);
+        // Surplus (6 - 3) of 6 passes on
+        assert_eq!(
+            decision,
+            RoundDecision::Elect { option: 0, transfer_ratio: (3 * scale - 1) as u64 / 6 }
+        );
+        assert_eq!(stv_decision(&[0, 2 * scale, scale], 1, 1, quota), RoundDecision::Eliminate(2));
+        assert_eq!(stv_decision(&[0, 2, 2], 0b1, 2, quota), RoundDecision::ElectRemaining);
+        assert_eq!(standings(&[5, 9, 5], 0), vec![1, 0, 2]);
+    }
+
+    #[test]
+    fn surplus_carries_ballots_to_later_preferences() {
+        // 10 votes for 2 seats, quota just over 10/3. Option 0's surplus of
+        // 6 - 10/3 carries option 1 past the quota, though option 2 had
+        // more first preferences
+        let ballots = [
+            (6, vec![0, 1]),
+            (1, vec![1]),
+            (2, vec![2]),
+            (1, vec![3]),
+        ];
+        assert_eq!(run_stv(&ballots, 4, 2), vec![0, 1]);
+
+        // With one seat the same ballots run as a single-winner contest
+        assert_eq!(run_stv(&ballots, 4, 1), vec![0]);
+    }
+
+    #[test]
+    fn eliminations_fill_the_last_seats() {
+        // 5 seats from 10 options; ballots of rising weight each rank an
+        // option then the next one
+        let ballots: Vec<(u64, Vec<u8>)> = (0..10u8)
+            .map(|option| (option as u64 + 1, vec![option, (option + 1) % 10]))
+            .collect();
+        // Only option 9 reaches the quota of 55/6 at first. Eliminating
+        // options 0, 2, 1 and 4 carries option 5 over it. Once option 3 is
+        // eliminated the last three seats go to the options left, in order
+        // of their tallies
+        assert_eq!(run_stv(&ballots, 10, 5), vec![9, 5, 8, 7, 6]);
+    }
+
+    #[test]
+    fn surplus_transfers_rewrite_the_buffer_across_slices() {
+        // Option 0's five ballots are spread over every slice of four
+        let ballots = [
+            (1, vec![0, 1]),
+            (1, vec![1]),
+            (1, vec![0, 1]),
+            (1, vec![2]),
+            (1, vec![0, 1]),
+            (1, vec![3]),
+            (1, vec![0, 1]),
+            (1, vec![2]),
+            (1, vec![0, 1]),
+        ];
+        let mut buffer = entries(&ballots, 4);
+        let mut results = fresh_results(4);
+        let method = VotingMethod::SingleTransferableVote { seats: 2 };
+        let scale = SCALE as u128;
+        let crank = |results: &mut ProposalResults, buffer: &mut Vec<u8>| {
+            count_slice(results, method, buffer, ballots.len() as u32, 4)
+        };
+
+        // Round 1 sets the quota from its 9 votes and elects option 0
+        assert_eq!(crank(&mut results, &mut buffer), None);
+        assert_eq!(crank(&mut results, &mut buffer), None);
+        let closed = crank(&mut results, &mut buffer).unwrap();
+        let quota = 3 * scale + 1;
+        let ratio = ((5 * scale - quota) * scale / (5 * scale)) as u64;
+        assert_eq!(results.quota, quota);
+        assert_eq!(closed, ClosedRound {
+            decision: RoundDecision::Elect { option: 0, transfer_ratio: ratio },
+            elected: vec![0],
+        });
+        assert_eq!(results.transfer, Some(SurplusTransfer { option: 0, ratio }));
+        assert_eq!(read_ballot(&buffer, 0).transfer_value, SCALE);
+
+        // The first slice of round 2 passes on the surplus of its ballots
+        // for option 0 only
+        assert_eq!(crank(&mut results, &mut buffer), None);
+        assert_eq!(read_ballot(&buffer, 0).transfer_value, ratio);
+        assert_eq!(read_ballot(&buffer, 2).transfer_value, ratio);
+        assert_eq!(read_ballot(&buffer, 1).transfer_value, SCALE);
+        assert_eq!(read_ballot(&buffer, 4).transfer_value, SCALE);
+        assert_eq!(results.round_tallies[1], scale + 2 * ratio as u128);
+
+        // ...and the next slices the rest, each ballot scaled once
+        assert_eq!(crank(&mut results, &mut buffer), None);
+        assert_eq!(results.round_tallies[1], scale + 4 * ratio as u128);
+        let closed = crank(&mut results, &mut buffer).unwrap();
+        for index in [0, 2, 4, 6, 8] {
+            assert_eq!(read_ballot(&buffer, index).transfer_value, ratio);
+        }
+        // Option 1 falls just short of the quota, so last-placed 3 goes
+        assert_eq!(closed.decision, RoundDecision::Eliminate(3));
+        assert_eq!(results.rounds[1].tallies, vec![0, 2, 2, 1]);
+        assert_eq!(results.transfer, None);
+
+        // Later rounds keep the scaled values and the first round's quota,
+        // though option 3's ballot is exhausted
+        while !results.tally_complete {
+            crank(&mut results, &mut buffer);
+        }
+        assert_eq!(read_ballot(&buffer, 8).transfer_value, ratio);
+        assert_eq!(results.quota, quota);
+        assert_eq!(results.rounds[2].exhausted_weight, 1);
+        assert_eq!(results.rounds[2].eliminated, Some(2));
+        // The surplus carried option 1 past option 2's two first preferences
+        assert_eq!(results.elected, vec![0, 1]);
+    }
+
+    #[test]
+    fn remaining_options_fill_the_last_seats_at_once() {
+        let ballots = [(1, vec![0]), (2, vec![1]), (1, vec![2]), (1, vec![3])];
+        let mut buffer = entries(&ballots, 4);
+        let mut results = fresh_results(4);
+        let method = VotingMethod::SingleTransferableVote { seats: 3 };
+        let mut decisions = Vec::new();
+        while !results.tally_complete {
+            decisions.push(count_slice(&mut results, method, &mut buffer, 4, 10).unwrap());
+        }
+
+        // Option 1 reaches the quota of 5/4; its surplus has no later
+        // preference to pass on to. The three left tie, so the
+        // highest-indexed is eliminated and the other two are elected
+        // together, lowest index first
+        let scale = SCALE as u128;
+        let ratio = ((2 * scale - droop_quota(5 * scale, 3)) * scale / (2 * scale)) as u64;
+        assert_eq!(decisions[0].decision, RoundDecision::Elect { option: 1, transfer_ratio: ratio });
+        assert_eq!(read_ballot(&buffer, 1).transfer_value, ratio);
+        assert_eq!(decisions[1].decision, RoundDecision::Eliminate(3));
+        assert_eq!(
+            decisions[2],
+            ClosedRound { decision: RoundDecision::ElectRemaining, elected: vec![0, 2] }
+        );
+        assert_eq!(results.rounds[2].elected, None);
+        assert_eq!(results.elected, vec![1, 0, 2]);
+    }
+}
@@ -0,0 +1,378 @@
+//! Vote weighting and proposal outcome computation
+//!
+//! Each vote adds its weight to the tally of its option, or of every option it
+//! approves under approval voting: 1, or a weight derived from the voter's
+//! token balance, square-rooted under quadratic voting. At finalize the
+//! counted weight is checked against the proposal's quorum and approval
+//! threshold. The winner is the option with the strictly highest tally; the
+//! proposal passes if that tally's share of the counted weight meets the
+//! threshold.
+
+use anchor_lang::prelude::*;
+
+/// Basis points in 100%
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Minimum participation for a proposal's result to count
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum Quorum {
+    /// At least this many counted votes
+    Votes(u64),
+    /// Counted votes of at least this share of the token mint's supply,
+    /// in basis points
+    SupplyBps(u16),
+}
+
+/// Share of the counted votes the winning option needs
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum ApprovalThreshold {
+    /// Strictly more than half
+    SimpleMajority,
+    /// At least this many basis points
+    Supermajority(u16),
+}
+
+/// How much a single vote counts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum VoteWeighting {
+    /// Every vote counts 1
+    OnePerVoter,
+    /// A vote counts `balance / unit`, at most `cap`; `unit` buckets the
+    /// balance (e.g. whole tokens) so the weight reveals less of it
+    TokenWeighted { unit: u64, cap: Option<u64> },
+}
+
+/// How a vote's weight is counted toward its option
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum VotingMethod {
+    /// The full weight goes to the chosen option
+    Plurality,
+    /// The integer square root of the weight goes to the chosen option, so
+    /// large holders gain influence sublinearly
+    Quadratic,
+    /// The ballot is a bitmask of approved options and the full weight goes
+    /// to each of them
+    Approval,
+    /// The ballot ranks the options and is counted by instant runoff (see
+    /// `ranked`)
+    RankedChoice,
+    /// Ranked ballots elect `seats` options by single transferable vote
+    /// (see `ranked`)
+    SingleTransferableVote { seats: u8 },
+}
+
+/// Result of a finalized proposal
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum ProposalOutcome {
+    /// The winning option met the approval threshold
+    Passed,
+    /// No option met the approval threshold
+    Rejected,
+    /// Too few votes were counted
+    QuorumNotMet,
+    /// Two or more options share the highest tally
+    Tie,
+}
+
+impl Quorum {
+    /// Whether the bounds are usable
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            Quorum::Votes(votes) => votes > 0,
+            Quorum::SupplyBps(bps) => bps > 0 && bps as u64 <= BPS_DENOMINATOR,
+        }
+    }
+
+    /// Whether the quorum can be measured in `weighting`'s units; a supply
+    /// share needs token weights, since one vote per voter cannot add up to
+    /// the token supply
+    pub fn supports(&self, weighting: VoteWeighting) -> bool {
+        !matches!(self, Quorum::SupplyBps(_)) || weighting != VoteWeighting::OnePerVoter
+    }
+
+    /// Whether `counted` votes meet the quorum; `supply` is the mint's total
+    /// supply and only read for `SupplyBps`
+    pub fn is_met(&self, counted: u64, supply: u64) -> bool {
+        match *self {
+            Quorum::Votes(votes) => counted >= votes,
+            Quorum::SupplyBps(bps) => {
+                counted as u128 * BPS_DENOMINATOR as u128 >= supply as u128 * bps as u128
+            }
+        }
+    }
+}
+
+impl VoteWeighting {
+    /// Whether the bounds are usable
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            VoteWeighting::OnePerVoter => true,
+            VoteWeighting::TokenWeighted { unit, cap } => unit > 0 && cap != Some(0),
+        }
+    }
+
+    /// Weight of a vote backed by `balance` tokens
+    pub fn weight(&self, balance: u64) -> u64 {
+        match *self {
+            VoteWeighting::OnePerVoter => 1,
+            VoteWeighting::TokenWeighted { unit, cap } => {
+                let weight = balance / unit;
+                match cap {
+                    Some(cap) => weight.min(cap),
+                    None => weight,
+                }
+            }
+        }
+    }
+
+    /// Token supply in weight units, for supply-based quorums
+    pub fn supply_weight(&self, supply: u64) -> u64 {
+        match *self {
+            VoteWeighting::OnePerVoter => supply,
+            VoteWeighting::TokenWeighted { unit, .. } => supply / unit,
+        }
+    }
+}
+
+impl VotingMethod {
+    /// Whether the method can be used with `num_options` options; STV must
+    /// leave at least one option unelected
+    pub fn is_valid(&self, num_options: usize) -> bool {
+        match *self {
+            VotingMethod::SingleTransferableVote { seats } => {
+                seats > 0 && (seats as usize) < num_options
+            }
+            _ => true,
+        }
+    }
+
+    /// Whether ballots are rankings revealed into a `RankedBallots` buffer
+    pub fn is_ranked(&self) -> bool {
+        matches!(
+            self,
+            VotingMethod::RankedChoice | VotingMethod::SingleTransferableVote { .. }
+        )
+    }
+
+    /// Weight counted for a vote with token weight `weight`
+    pub fn effective_weight(&self, weight: u64) -> u64 {
+        match self {
+            VotingMethod::Quadratic => isqrt(weight),
+            _ => weight,
+        }
+    }
+
+    /// Whether `ballot` is an option index, or for approval voting a
+    /// non-empty bitmask over the options; ranked ballots are never a single
+    /// value
+    pub fn is_valid_ballot(&self, ballot: u16, num_options: usize) -> bool {
+        match self {
+            VotingMethod::Plurality | VotingMethod::Quadratic => (ballot as usize) < num_options,
+            VotingMethod::Approval => ballot != 0 && (ballot as usize) < 1 << num_options,
+            VotingMethod::RankedChoice | VotingMethod::SingleTransferableVote { .. } => false,
+        }
+    }
+
+    /// Add a valid ballot's weight to the tallies
+    pub fn count_ballot(&self, tallies: &mut [u64], ballot: u16, weight: u64) {
+        match self {
+            VotingMethod::Plurality | VotingMethod::Quadratic => tallies[ballot as usize] += weight,
+            VotingMethod::Approval => {
+                for (option, tally) in tallies.iter_mut().enumerate() {
+                    if ballot & (1 << option) != 0 {
+                        *tally += weight;
+                    }
+                }
+            }
+            // Counted round by round by the ranked tally crank
+            VotingMethod::RankedChoice | VotingMethod::SingleTransferableVote { .. } => {}
+        }
+    }
+}
+
+/// Largest `r` with `r * r <= n`
+pub fn isqrt(n: u64) -> u64 {
+    let n = n as u128;
+    let mut root = n;
+    let mut next = root.div_ceil(2);
+    while next < root {
+        root = next;
+        next = (root + n / root) / 2;
+    }
+    root as u64
+}
+
+impl ApprovalThreshold {
+    /// Whether the bounds are usable
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            ApprovalThreshold::SimpleMajority => true,
+            ApprovalThreshold::Supermajority(bps) => bps > 0 && bps as u64 <= BPS_DENOMINATOR,
+        }
+    }
+
+    /// Whether `votes` out of `counted` meet the threshold
+    pub fn is_met(&self, votes: u64, counted: u64) -> bool {
+        match *self {
+            ApprovalThreshold::SimpleMajority => votes as u128 * 2 > counted as u128,
+            ApprovalThreshold::Supermajority(bps) => {
+                votes as u128 * BPS_DENOMINATOR as u128 >= counted as u128 * bps as u128
+            }
+        }
+    }
+}
+
+/// The option with the strictly highest tally, or `None` on a tie
+pub fn leading_option(tallies: &[u64]) -> Option<u8> {
+    let max = *tallies.iter().max()?;
+    let mut leaders = tallies.iter().enumerate().filter(|(_, tally)| **tally == max);
+    let (index, _) = leaders.next()?;
+    if leaders.next().is_some() {
+        return None;
+    }
+    Some(index as u8)
+}
+
+/// Compute the outcome and winning option from final tallies
+///
+/// `counted` is the weight of the ballots in the tallies, which is less than
+/// their sum when a ballot approves several options. `quorum_met` is
+/// evaluated by the caller, which has access to the mint.
+/// The winning option is reported whenever there is a unique leader, even if
+/// it fell short of the threshold.
+pub fn compute_outcome(
+    tallies: &[u64],
+    counted: u64,
+    quorum_met: bool,
+    threshold: ApprovalThreshold,
+) -> (ProposalOutcome, Option<u8>) {
+    if !quorum_met {
+        return (ProposalOutcome::QuorumNotMet, None);
+    }
+
+    let Some(winner) = leading_option(tallies) else {
+        return (ProposalOutcome::Tie, None);
+    };
+
+    if threshold.is_met(tallies[winner as usize], counted) {
+        (ProposalOutcome::Passed, Some(winner))
+    } else {
+        (ProposalOutcome::Rejected, Some(winner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simple_majority_needs_more_than_half() {
+        assert_eq!(
+            compute_outcome(&[6, 4], 10, true, ApprovalThreshold::SimpleMajority),
+            (ProposalOutcome::Passed, Some(0))
+        );
+        assert_eq!(
+            compute_outcome(&[5, 3, 2], 10, true, ApprovalThreshold::SimpleMajority),
+            (ProposalOutcome::Rejected, Some(0))
+        );
+    }
+
+    #[test]
+    fn supermajority_is_inclusive() {
+        let two_thirds = ApprovalThreshold::Supermajority(6_667);
+        assert_eq!(compute_outcome(&[1, 2], 3, true, two_thirds), (ProposalOutcome::Rejected, Some(1)));
+        assert_eq!(compute_outcome(&[3334, 6666], 10000, true, two_thirds), (ProposalOutcome::Rejected, Some(1)));
+        assert_eq!(compute_outcome(&[3333, 6667], 10000, true, two_thirds), (ProposalOutcome::Passed, Some(1)));
+    }
+
+    #[test]
+    fn ties_are_reported() {
+        assert_eq!(compute_outcome(&[3, 3, 1], 7, true, ApprovalThreshold::SimpleMajority), (ProposalOutcome::Tie, None));
+        assert_eq!(compute_outcome(&[0, 0], 0, true, ApprovalThreshold::SimpleMajority), (ProposalOutcome::Tie, None));
+    }
+
+    #[test]
+    fn quorum_is_checked_first() {
+        assert_eq!(
+            compute_outcome(&[9, 0], 9, false, ApprovalThreshold::SimpleMajority),
+            (ProposalOutcome::QuorumNotMet, None)
+        );
+    }
+
+    #[test]
+    fn quorum_bounds() {
+        assert!(Quorum::Votes(3).is_met(3, 0));
+        assert!(!Quorum::Votes(3).is_met(2, 0));
+        // 10% of 1000
+        assert!(Quorum::SupplyBps(1_000).is_met(100, 1_000));
+        assert!(!Quorum::SupplyBps(1_000).is_met(99, 1_000));
+        assert!(Quorum::SupplyBps(10_000).is_met(u64::MAX, u64::MAX));
+
+        assert!(!Quorum::Votes(0).is_valid());
+        assert!(!Quorum::SupplyBps(10_001).is_valid());
+        assert!(!ApprovalThreshold::Supermajority(0).is_valid());
+    }
+
+    #[test]
+    fn supply_quorum_needs_token_weighting() {
+        let tokens = VoteWeighting::TokenWeighted { unit: 1, cap: None };
+        assert!(Quorum::Votes(3).supports(VoteWeighting::OnePerVoter));
+        assert!(Quorum::Votes(3).supports(tokens));
+        assert!(Quorum::SupplyBps(1_000).supports(tokens));
+        assert!(!Quorum::SupplyBps(1_000).supports(VoteWeighting::OnePerVoter));
+    }
+
+    #[test]
+    fn token_weights_are_bucketed_and_capped() {
+        assert_eq!(VoteWeighting::OnePerVoter.weight(1_000_000), 1);
+
+        let whole_tokens = VoteWeighting::TokenWeighted { unit: 1_000, cap: None };
+        assert_eq!(whole_tokens.weight(999), 0);
+        assert_eq!(whole_tokens.weight(2_500), 2);
+        assert_eq!(whole_tokens.supply_weight(1_000_000), 1_000);
+
+        let capped = VoteWeighting::TokenWeighted { unit: 1, cap: Some(100) };
+        assert_eq!(capped.weight(99), 99);
+        assert_eq!(capped.weight(u64::MAX), 100);
+
+        assert!(!VoteWeighting::TokenWeighted { unit: 0, cap: None }.is_valid());
+        assert!(!VoteWeighting::TokenWeighted { unit: 1, cap: Some(0) }.is_valid());
+    }
+
+    #[test]
+    fn quadratic_weight_is_floor_square_root() {
+        for (n, root) in [(0, 0), (1, 1), (3, 1), (4, 2), (15, 3), (16, 4), (1_000_000, 1_000)] {
+            assert_eq!(isqrt(n), root);
+        }
+        assert_eq!(isqrt(u64::MAX), u32::MAX as u64);
+        assert_eq!(VotingMethod::Quadratic.effective_weight(99), 9);
+        assert_eq!(VotingMethod::Plurality.effective_weight(99), 99);
+    }
+
+    #[test]
+    fn approval_ballots_count_every_approved_option() {
+        let approval = VotingMethod::Approval;
+        assert!(approval.is_valid_ballot(0b111, 3));
+        assert!(!approval.is_valid_ballot(0b1000, 3));
+        // An empty approval would count toward quorum without backing any
+        // option
+        assert!(!approval.is_valid_ballot(0, 3));
+        assert!(!VotingMethod::Plurality.is_valid_ballot(3, 3));
+
+        let mut tallies = [0; 3];
+        approval.count_ballot(&mut tallies, 0b101, 2);
+        approval.count_ballot(&mut tallies, 0b001, 1);
+        assert_eq!(tallies, [3, 0, 2]);
+
+        // Option 0 is approved on two of three ballots, a majority of the
+        // counted weight though not of the summed tallies
+        assert_eq!(
+            compute_outcome(&[2, 1, 2], 3, true, ApprovalThreshold::SimpleMajority),
+            (ProposalOutcome::Tie, None)
+        );
+        assert_eq!(
+            compute_outcome(&[2, 1, 1], 3, true, ApprovalThreshold::SimpleMajority),
+            (ProposalOutcome::Passed, Some(0))
+        );
+    }
+}
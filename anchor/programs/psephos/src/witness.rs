@@ -0,0 +1,276 @@
+//! Strict parser for gnark public witnesses
+//!
+//! gnark serializes a public witness as
+//!
+//! ```text
+//! nb_public: u32 BE || nb_secret: u32 BE || len: u32 BE || len * 32-byte BE field elements
+//! ```
+//!
+//! For a public witness `nb_secret` is 0 and `len == nb_public`. Parsing is
+//! exact: the header must be self-consistent, there must be no trailing
+//! bytes, and every element must be a canonical BN254 scalar (< r).
+
+use anchor_lang::prelude::*;
+
+use crate::groth16::is_canonical_scalar;
+use crate::{PsephosError, FIELD_ELEMENT_SIZE, PUBLIC_WITNESS_HEADER_SIZE};
+
+/// Why a public witness was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WitnessError {
+    /// Fewer bytes than the 12-byte header
+    TruncatedHeader,
+    /// Header public-input count differs from what the circuit exposes
+    InputCountMismatch { expected: usize, actual: usize },
+    /// Header declares secret inputs; a public witness has none
+    UnexpectedSecretInputs,
+    /// Vector length in the header differs from the public-input count
+    VectorLengthMismatch,
+    /// Byte length does not match the header (truncated or trailing bytes)
+    LengthMismatch { expected: usize, actual: usize },
+    /// Element is not less than the BN254 scalar modulus
+    NonCanonicalElement { index: usize },
+    /// Element expected to hold an integer has nonzero high bytes
+    ValueOutOfRange { index: usize },
+    /// Element index past the end of the witness
+    IndexOutOfBounds { index: usize },
+}
+
+impl From<WitnessError> for Error {
+    fn from(error: WitnessError) -> Self {
+        msg!("Public witness rejected: {:?}", error);
+        match error {
+            WitnessError::TruncatedHeader | WitnessError::LengthMismatch { .. } => {
+                PsephosError::WitnessLengthInvalid.into()
+            }
+            WitnessError::InputCountMismatch { .. }
+            | WitnessError::UnexpectedSecretInputs
+            | WitnessError::VectorLengthMismatch => PsephosError::WitnessHeaderInvalid.into(),
+            WitnessError::NonCanonicalElement { .. } => PsephosError::NonCanonicalFieldElement.into(),
+            WitnessError::ValueOutOfRange { .. } => PsephosError::WitnessValueOutOfRange.into(),
+            WitnessError::IndexOutOfBounds { .. } => PsephosError::InvalidPublicWitness.into(),
+        }
+    }
+}
+
+/// A parsed gnark public witness
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicWitness {
+    elements: Vec<[u8; FIELD_ELEMENT_SIZE]>,
+}
+
+impl PublicWitness {
+    /// Parse a witness, taking the input count from its header
+    pub fn parse(bytes: &[u8]) -> core::result::Result<Self, WitnessError> {
+        if bytes.len() < PUBLIC_WITNESS_HEADER_SIZE {
+            return Err(WitnessError::TruncatedHeader);
+        }
+
+        let nb_public = read_u32(&bytes[0..4]) as usize;
+        let nb_secret = read_u32(&bytes[4..8]);
+        let vector_len = read_u32(&bytes[8..12]) as usize;
+
+        if nb_secret != 0 {
+            return Err(WitnessError::UnexpectedSecretInputs);
+        }
+        if vector_len != nb_public {
+            return Err(WitnessError::VectorLengthMismatch);
+        }
+
+        let body = &bytes[PUBLIC_WITNESS_HEADER_SIZE..];
+        let expected = nb_public
+            .checked_mul(FIELD_ELEMENT_SIZE)
+            .and_then(|size| size.checked_add(PUBLIC_WITNESS_HEADER_SIZE))
+            .ok_or(WitnessError::LengthMismatch { expected: usize::MAX, actual: bytes.len() })?;
+        if bytes.len() != expected {
+            return Err(WitnessError::LengthMismatch { expected, actual: bytes.len() });
+        }
+
+        let mut elements = Vec::with_capacity(nb_public);
+        for (index, chunk) in body.chunks_exact(FIELD_ELEMENT_SIZE).enumerate() {
+            let mut element = [0u8; FIELD_ELEMENT_SIZE];
+            element.copy_from_slice(chunk);
+            if !is_canonical_scalar(&element) {
+                return Err(WitnessError::NonCanonicalElement { index });
+            }
+            elements.push(element);
+        }
+
+        Ok(Self { elements })
+    }
+
+    /// Parse a witness that must expose exactly `expected_inputs` public inputs
+    pub fn parse_expected(bytes: &[u8], expected_inputs: usize) -> core::result::Result<Self, WitnessError> {
+        if bytes.len() >= 4 {
+            let actual = read_u32(&bytes[0..4]) as usize;
+            if actual != expected_inputs {
+                return Err(WitnessError::InputCountMismatch { expected: expected_inputs, actual });
+            }
+        }
+        Self::parse(bytes)
+    }
+
+    /// Number of public inputs
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Whether the witness has no public inputs
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// All public inputs in circuit order
+    pub fn elements(&self) -> &[[u8; FIELD_ELEMENT_SIZE]] {
+        &self.elements
+    }
+
+    /// Public input `index` as a raw field element
+    pub fn field(&self, index: usize) -> core::result::Result<&[u8; FIELD_ELEMENT_SIZE], WitnessError> {
+        self.elements.get(index).ok_or(WitnessError::IndexOutOfBounds { index })
+    }
+
+    /// Public input `index` as a u64, rejecting values with nonzero high bytes
+    pub fn u64_at(&self, index: usize) -> core::result::Result<u64, WitnessError> {
+        let element = self.field(index)?;
+        let (high, low) = element.split_at(FIELD_ELEMENT_SIZE - 8);
+        if high.iter().any(|byte| *byte != 0) {
+            return Err(WitnessError::ValueOutOfRange { index });
+        }
+        let mut value = [0u8; 8];
+        value.copy_from_slice(low);
+        Ok(u64::from_be_bytes(value))
+    }
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(nb_public: u32, nb_secret: u32, vector_len: u32) -> Vec<u8> {
+        [nb_public.to_be_bytes(), nb_secret.to_be_bytes(), vector_len.to_be_bytes()].concat()
+    }
+
+    fn field(value: u64) -> [u8; 32] {
+        let mut element = [0u8; 32];
+        element[24..].copy_from_slice(&value.to_be_bytes());
+        element
+    }
+
+    fn witness(elements: &[[u8; 32]]) -> Vec<u8> {
+        let n = elements.len() as u32;
+        let mut bytes = header(n, 0, n);
+        for element in elements {
+            bytes.extend_from_slice(element);
+        }
+        bytes
+    }
+
+    #[test]
+    fn parses_well_formed_witness() {
+        let bytes = witness(&[field(50), field(1), [7u8; 32], [9u8; 32]]);
+        let parsed = PublicWitness::parse_expected(&bytes, 4).unwrap();
+
+        assert_eq!(parsed.len(), 4);
+        assert_eq!(parsed.u64_at(0), Ok(50));
+        assert_eq!(parsed.u64_at(1), Ok(1));
+        assert_eq!(parsed.field(2), Ok(&[7u8; 32]));
+        assert_eq!(parsed.field(3), Ok(&[9u8; 32]));
+    }
+
+    #[test]
+    fn rejects_truncated_header() {
+        assert_eq!(PublicWitness::parse(&[0, 0, 0, 1]), Err(WitnessError::TruncatedHeader));
+    }
+
+    #[test]
+    fn rejects_wrong_input_count() {
+        let bytes = witness(&[field(1), field(2), field(3)]);
+        assert_eq!(
+            PublicWitness::parse_expected(&bytes, 4),
+            Err(WitnessError::InputCountMismatch { expected: 4, actual: 3 })
+        );
+    }
+
+    #[test]
+    fn rejects_secret_inputs_in_header() {
+        let mut bytes = header(1, 1, 1);
+        bytes.extend_from_slice(&field(1));
+        assert_eq!(PublicWitness::parse(&bytes), Err(WitnessError::UnexpectedSecretInputs));
+    }
+
+    #[test]
+    fn rejects_inconsistent_vector_length() {
+        let mut bytes = header(2, 0, 1);
+        bytes.extend_from_slice(&field(1));
+        bytes.extend_from_slice(&field(2));
+        assert_eq!(PublicWitness::parse(&bytes), Err(WitnessError::VectorLengthMismatch));
+    }
+
+    #[test]
+    fn rejects_trailing_bytes() {
+        let mut bytes = witness(&[field(1)]);
+        bytes.push(0);
+        assert_eq!(
+            PublicWitness::parse(&bytes),
+            Err(WitnessError::LengthMismatch { expected: 44, actual: 45 })
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_elements() {
+        let mut bytes = witness(&[field(1), field(2)]);
+        bytes.truncate(bytes.len() - 1);
+        assert_eq!(
+            PublicWitness::parse(&bytes),
+            Err(WitnessError::LengthMismatch { expected: 76, actual: 75 })
+        );
+    }
+
+    #[test]
+    fn rejects_non_canonical_elements() {
+        let modulus = crate::groth16::SCALAR_FIELD_MODULUS;
+        let bytes = witness(&[field(1), modulus]);
+        assert_eq!(PublicWitness::parse(&bytes), Err(WitnessError::NonCanonicalElement { index: 1 }));
+
+        let bytes = witness(&[[0xff; 32]]);
+        assert_eq!(PublicWitness::parse(&bytes), Err(WitnessError::NonCanonicalElement { index: 0 }));
+    }
+
+    #[test]
+    fn accepts_largest_canonical_element() {
+        let mut largest = crate::groth16::SCALAR_FIELD_MODULUS;
+        largest[31] -= 1;
+        assert!(PublicWitness::parse(&witness(&[largest])).is_ok());
+    }
+
+    #[test]
+    fn rejects_u64_with_high_bytes_set() {
+        let mut element = field(50);
+        element[0] = 1;
+        let parsed = PublicWitness::parse(&witness(&[element])).unwrap();
+        assert_eq!(parsed.u64_at(0), Err(WitnessError::ValueOutOfRange { index: 0 }));
+
+        // The byte just above the u64 range also counts as a high byte
+        let mut element = field(50);
+        element[23] = 1;
+        let parsed = PublicWitness::parse(&witness(&[element])).unwrap();
+        assert_eq!(parsed.u64_at(0), Err(WitnessError::ValueOutOfRange { index: 0 }));
+    }
+
+    #[test]
+    fn rejects_out_of_bounds_index() {
+        let parsed = PublicWitness::parse(&witness(&[field(1)])).unwrap();
+        assert_eq!(parsed.field(1), Err(WitnessError::IndexOutOfBounds { index: 1 }));
+    }
+
+    #[test]
+    fn rejects_header_count_overflow() {
+        let bytes = header(u32::MAX, 0, u32::MAX);
+        assert!(matches!(PublicWitness::parse(&bytes), Err(WitnessError::LengthMismatch { .. })));
+    }
+}
@@ -4,6 +4,7 @@ import { PublicKey, Keypair, LAMPORTS_PER_SOL, SystemProgram, ComputeBudgetProgr
 import {
   createMint,
   createAssociatedTokenAccount,
+  getAccount,
   mintTo,
   TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
@@ -12,21 +13,39 @@ import { Psephos } from "../target/types/psephos";
 import * as fs from 'fs';
 import * as path from 'path';
 
-// Load pre-generated proof and witness for real ZK verification
+// Load the proof and witness built from circuits/Prover.toml by
+// scripts/build-circuit-fixtures.sh (run by `anchor test`)
 const circuitDir = path.join(__dirname, '../../circuits/target');
 const realProof = fs.readFileSync(path.join(circuitDir, 'psephos_circuits.proof'));
 const realWitness = fs.readFileSync(path.join(circuitDir, 'psephos_circuits.pw'));
 
-// Known values from Prover.toml (these match the pre-generated proof)
-const KNOWN_NULLIFIER = Buffer.from('0a2117377b0ea781202c90d57ddc28c4a98ad83879c0bc1132cca576ff99e9bf', 'hex');
-const KNOWN_COMMITMENT = Buffer.from('1f1dd08a1cb204c943c29bfdbd6e96eda795980a7521f1479f43c33dd56d9a32', 'hex');
+// Known values from Prover.toml (these match the generated proof)
+const KNOWN_NULLIFIER = Buffer.from('20b71c3f5b473485eedd12cfc74e7bfa90f2b143422a33218545ac4d19a21e89', 'hex');
+const KNOWN_COMMITMENT = Buffer.from('18bae0e1cb3ba1364d149ab311c2267c7460a3a025d3e06604ec00a62a17103f', 'hex');
 
-// Values from Prover.toml that the proof was generated with
+// Values from Prover.toml that the proof was generated with. Its
+// proposal_domain is the address of the test space's first proposal, which
+// is fixed by the creator's seed below
 const PROOF_PROPOSAL_ID = new BN(1);
 const PROOF_THRESHOLD = new BN(50);
+const CREATOR_SEED = Buffer.alloc(32, 1);
+
+// BN254 scalar field modulus
+const FIELD_MODULUS = BigInt("0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001");
+
+// A proposal's circuit domain: its address reduced into the scalar field
+const proposalDomain = (proposal: PublicKey): Buffer => {
+  const domain = BigInt("0x" + proposal.toBuffer().toString("hex")) % FIELD_MODULUS;
+  return Buffer.from(domain.toString(16).padStart(64, "0"), "hex");
+};
 
 // ZK Verifier Program ID (circuit-specific Sunspot verifier for psephos_circuits)
 const ZK_VERIFIER_PROGRAM_ID = new PublicKey("G616ZLAnrgeb7FrAvavozAyKmgzsuncz1XTvBYiUzh4H");
+// ZK Verifier Program ID for the reveal circuit (circuits/reveal)
+const REVEAL_VERIFIER_PROGRAM_ID = new PublicKey("8Y427kK6zcvjL5j1y661sUydBvkBZE5pyjLuEPdJn1UD");
+// ZK Verifier Program ID for the snapshot circuit (circuits/snapshot)
+const SNAPSHOT_VERIFIER_PROGRAM_ID = new PublicKey("5zBsee3vVmqja26FsejAcfZktBbNAP6PCySvVhbWvqA5");
+const BPF_LOADER_UPGRADEABLE_PROGRAM_ID = new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111");
 
 describe("psephos", () => {
   const provider = anchor.AnchorProvider.env();
@@ -34,18 +53,56 @@ describe("psephos", () => {
 
   const program = anchor.workspace.psephos as Program<Psephos>;
 
-  // Test keypairs
-  const creator = Keypair.generate();
+  // Test keypairs; the creator is fixed by seed (see Prover.toml)
+  const creator = Keypair.fromSeed(CREATOR_SEED);
   const voter1 = Keypair.generate();
 
   // Token mint and accounts
   let tokenMint: PublicKey;
   let voter1TokenAccount: PublicKey;
 
+  // Governance space holding every test proposal
+  const SPACE_NAME = "psephos-tests";
+  const PROPOSAL_DEPOSIT = new BN(LAMPORTS_PER_SOL / 100);
+  let spacePDA: PublicKey;
+  let treasuryPDA: PublicKey;
+
   // PDAs for the real ZK test (proposal ID = 1)
   let proposalPDA: PublicKey;
   let resultsPDA: PublicKey;
   let voteRecordPDA: PublicKey;
+  let verifierRegistryPDA: PublicKey;
+
+  // ID the space assigns to the next proposal
+  const nextProposalId = async () => {
+    const space = await program.account.governanceSpace.fetch(spacePDA);
+    return space.proposalCount.addn(1);
+  };
+
+  // Deposit vault of a proposal
+  const depositPDA = (proposal: PublicKey) =>
+    PublicKey.findProgramAddressSync([Buffer.from("deposit"), proposal.toBuffer()], program.programId)[0];
+
+  // Open space config: anyone can propose
+  const spaceConfig = () => ({
+    tokenMint,
+    minThreshold: PROOF_THRESHOLD,
+    quorum: null,
+    approvalThreshold: { simpleMajority: {} },
+    votingPeriodSeconds: new BN(5),
+    revealPeriodSeconds: new BN(60),
+    proposerMinBalance: null,
+    proposalDeposit: PROPOSAL_DEPOSIT,
+    executionDelaySeconds: new BN(0),
+  });
+
+  // Attached transaction of the real ZK proposal: option 1 pays from the treasury
+  const TREASURY_PAYOUT = 1000;
+  const transactionPDA = (proposal: PublicKey, option: number, index: number) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("transaction"), proposal.toBuffer(), Buffer.from([option]), Buffer.from([index])],
+      program.programId
+    )[0];
 
   before(async () => {
     // Fund test accounts
@@ -88,14 +145,34 @@ describe("psephos", () => {
       100
     );
 
+    // Create the space; its first proposal gets ID 1 (matches Prover.toml)
+    [spacePDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("space"), creator.publicKey.toBuffer(), Buffer.from(SPACE_NAME)],
+      program.programId
+    );
+    [treasuryPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("treasury"), spacePDA.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .createSpace(SPACE_NAME, [creator.publicKey], [], spaceConfig())
+      .accounts({
+        creator: creator.publicKey,
+        governanceSpace: spacePDA,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([creator])
+      .rpc();
+
     // Derive PDAs for proposal ID = 1 (matches Prover.toml)
     [proposalPDA] = PublicKey.findProgramAddressSync(
-      [Buffer.from("proposal"), PROOF_PROPOSAL_ID.toArrayLike(Buffer, "le", 8)],
+      [Buffer.from("proposal"), spacePDA.toBuffer(), PROOF_PROPOSAL_ID.toArrayLike(Buffer, "le", 8)],
       program.programId
     );
 
     [resultsPDA] = PublicKey.findProgramAddressSync(
-      [Buffer.from("results"), PROOF_PROPOSAL_ID.toArrayLike(Buffer, "le", 8)],
+      [Buffer.from("results"), spacePDA.toBuffer(), PROOF_PROPOSAL_ID.toArrayLike(Buffer, "le", 8)],
       program.programId
     );
 
@@ -104,27 +181,236 @@ describe("psephos", () => {
       program.programId
     );
 
+    // Register the voting circuit verifiers; the provider wallet is the upgrade authority
+    [verifierRegistryPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("verifier_registry")],
+      program.programId
+    );
+    const [programDataPDA] = PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+    );
+
+    await program.methods
+      .initVerifierRegistry()
+      .accounts({
+        admin: provider.wallet.publicKey,
+        verifierRegistry: verifierRegistryPDA,
+        program: program.programId,
+        programData: programDataPDA,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .addVerifier(ZK_VERIFIER_PROGRAM_ID, 1, { eligibility: {} })
+      .accounts({ admin: provider.wallet.publicKey, verifierRegistry: verifierRegistryPDA })
+      .rpc();
+
+    await program.methods
+      .addVerifier(SNAPSHOT_VERIFIER_PROGRAM_ID, 1, { snapshot: {} })
+      .accounts({ admin: provider.wallet.publicKey, verifierRegistry: verifierRegistryPDA })
+      .rpc();
+
+    await program.methods
+      .addVerifier(REVEAL_VERIFIER_PROGRAM_ID, 1, { reveal: {} })
+      .accounts({ admin: provider.wallet.publicKey, verifierRegistry: verifierRegistryPDA })
+      .rpc();
+
     console.log("Token mint:", tokenMint.toBase58());
     console.log("Voter1 token account:", voter1TokenAccount.toBase58());
     console.log("Proposal PDA:", proposalPDA.toBase58());
   });
 
+  describe("verifying keys", () => {
+    it("should reject a verifying key from a non-admin", async () => {
+      const keyId = new BN(1);
+      const [verifyingKeyPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("verifying_key"), keyId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .initVerifyingKey(
+            keyId,
+            Array(64).fill(0),
+            Array(128).fill(0),
+            Array(128).fill(0),
+            Array(128).fill(0),
+            [Array(64).fill(0), Array(64).fill(0)],
+            null
+          )
+          .accounts({
+            admin: voter1.publicKey,
+            verifierRegistry: verifierRegistryPDA,
+            verifyingKey: verifyingKeyPDA,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([voter1])
+          .rpc();
+
+        assert.fail("Should have thrown Unauthorized error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+  });
+
+  describe("governance spaces", () => {
+    it("should store the space configuration", async () => {
+      const space = await program.account.governanceSpace.fetch(spacePDA);
+      assert.equal(space.name, SPACE_NAME);
+      assert.equal(space.admins.length, 1);
+      assert.equal(space.config.tokenMint.toBase58(), tokenMint.toBase58());
+      assert.equal(space.proposalCount.toString(), "0");
+    });
+
+    it("should reject space updates from non-admins", async () => {
+      try {
+        await program.methods
+          .updateSpace([voter1.publicKey], [], spaceConfig())
+          .accounts({ admin: voter1.publicKey, governanceSpace: spacePDA })
+          .signers([voter1])
+          .rpc();
+
+        assert.fail("Should have thrown Unauthorized error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+
+    it("should take SOL and token deposits into the treasury", async () => {
+      await program.methods
+        .depositToTreasury(new BN(LAMPORTS_PER_SOL / 10))
+        .accounts({
+          depositor: creator.publicKey,
+          governanceSpace: spacePDA,
+          treasury: treasuryPDA,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([creator])
+        .rpc();
+      assert.equal(await provider.connection.getBalance(treasuryPDA), LAMPORTS_PER_SOL / 10);
+
+      const [vaultPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("treasury_vault"), spacePDA.toBuffer(), tokenMint.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .initTreasuryVault()
+        .accounts({
+          payer: creator.publicKey,
+          governanceSpace: spacePDA,
+          treasury: treasuryPDA,
+          mint: tokenMint,
+          vault: vaultPDA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([creator])
+        .rpc();
+
+      const creatorTokenAccount = await createAssociatedTokenAccount(
+        provider.connection,
+        creator,
+        tokenMint,
+        creator.publicKey
+      );
+      await mintTo(provider.connection, creator, tokenMint, creatorTokenAccount, creator, 25);
+
+      await program.methods
+        .depositTokensToTreasury(new BN(25))
+        .accounts({
+          depositor: creator.publicKey,
+          governanceSpace: spacePDA,
+          mint: tokenMint,
+          depositorTokenAccount: creatorTokenAccount,
+          vault: vaultPDA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([creator])
+        .rpc();
+
+      const vault = await getAccount(provider.connection, vaultPDA);
+      assert.equal(vault.amount.toString(), "25");
+      assert.equal(vault.owner.toBase58(), treasuryPDA.toBase58());
+    });
+
+    it("should only let allowlisted accounts propose in a gated space", async () => {
+      const gatedName = "gated";
+      const [gatedSpacePDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("space"), voter1.publicKey.toBuffer(), Buffer.from(gatedName)],
+        program.programId
+      );
+      await program.methods
+        .createSpace(gatedName, [voter1.publicKey], [voter1.publicKey], spaceConfig())
+        .accounts({
+          creator: voter1.publicKey,
+          governanceSpace: gatedSpacePDA,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([voter1])
+        .rpc();
+
+      const firstId = new BN(1);
+      const [gatedProposalPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("proposal"), gatedSpacePDA.toBuffer(), firstId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [gatedResultsPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("results"), gatedSpacePDA.toBuffer(), firstId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .createProposal("Not allowed", ["A", "B"], null, null, null, null, ZK_VERIFIER_PROGRAM_ID, null, { commitReveal: {} }, { onePerVoter: {} }, { plurality: {} })
+          .accounts({
+            creator: creator.publicKey,
+            governanceSpace: gatedSpacePDA,
+            proposal: gatedProposalPDA,
+            results: gatedResultsPDA,
+            deposit: depositPDA(gatedProposalPDA),
+            creatorTokenAccount: null,
+            verifierRegistry: verifierRegistryPDA,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([creator])
+          .rpc();
+
+        assert.fail("Should have thrown ProposerNotEligible error");
+      } catch (error) {
+        expect(error.message).to.include("ProposerNotEligible");
+      }
+    });
+  });
+
   describe("create_proposal", () => {
     it("should create a proposal with valid parameters", async () => {
       // Create proposal with parameters matching Prover.toml
       const tx = await program.methods
         .createProposal(
-          PROOF_PROPOSAL_ID,
           "Real ZK Voting Test",
           ["Yes", "No", "Abstain"],
-          tokenMint,
-          PROOF_THRESHOLD,
-          new BN(5) // 5 seconds voting period
+          null, // voting opens immediately
+          null, // space default: 5 seconds voting period
+          null, // space default: 60 seconds to reveal after voting ends
+          null, // no eligibility snapshot: use voter token account
+          ZK_VERIFIER_PROGRAM_ID,
+          REVEAL_VERIFIER_PROGRAM_ID,
+          { commitReveal: {} },
+          { onePerVoter: {} },
+          { plurality: {} }
         )
         .accounts({
           creator: creator.publicKey,
+          governanceSpace: spacePDA,
           proposal: proposalPDA,
           results: resultsPDA,
+          deposit: depositPDA(proposalPDA),
+          creatorTokenAccount: null,
+          verifierRegistry: verifierRegistryPDA,
           systemProgram: SystemProgram.programId,
         })
         .signers([creator])
@@ -134,40 +420,84 @@ describe("psephos", () => {
 
       const proposal = await program.account.proposal.fetch(proposalPDA);
       assert.equal(proposal.id.toString(), PROOF_PROPOSAL_ID.toString());
+      assert.equal(proposal.governanceSpace.toBase58(), spacePDA.toBase58());
       assert.equal(proposal.minThreshold.toString(), PROOF_THRESHOLD.toString());
-      assert.equal(proposal.isFinalized, false);
+      assert.deepEqual(proposal.status, { active: {} });
+      assert.equal(proposal.revealEndTime.sub(proposal.endTime).toString(), "60");
       assert.equal(proposal.voteCount.toString(), "0");
+      assert.deepEqual(proposal.weighting, { onePerVoter: {} });
+      assert.deepEqual(proposal.votingMethod, { plurality: {} });
+      assert.equal(proposal.totalWeight.toString(), "0");
 
       const results = await program.account.proposalResults.fetch(resultsPDA);
       assert.equal(results.tallies.length, 3);
       assert.isTrue(results.tallies.every(t => t.toString() === "0"));
     });
 
+    it("should attach a treasury transaction to an option", async () => {
+      const transfer = SystemProgram.transfer({
+        fromPubkey: treasuryPDA,
+        toPubkey: creator.publicKey,
+        lamports: TREASURY_PAYOUT,
+      });
+
+      await program.methods
+        .insertTransaction(1, {
+          programId: transfer.programId,
+          accounts: transfer.keys.map(key => ({
+            pubkey: key.pubkey,
+            isSigner: key.isSigner,
+            isWritable: key.isWritable,
+          })),
+          data: transfer.data,
+        })
+        .accounts({
+          creator: creator.publicKey,
+          proposal: proposalPDA,
+          proposalTransaction: transactionPDA(proposalPDA, 1, 0),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([creator])
+        .rpc();
+
+      const proposal = await program.account.proposal.fetch(proposalPDA);
+      assert.deepEqual(Array.from(proposal.transactionCounts), [0, 1, 0]);
+    });
+
     it("should fail to create proposal with too few options", async () => {
-      const badProposalId = new BN(999);
+      const badProposalId = await nextProposalId();
       const [badProposalPDA] = PublicKey.findProgramAddressSync(
-        [Buffer.from("proposal"), badProposalId.toArrayLike(Buffer, "le", 8)],
+        [Buffer.from("proposal"), spacePDA.toBuffer(), badProposalId.toArrayLike(Buffer, "le", 8)],
         program.programId
       );
       const [badResultsPDA] = PublicKey.findProgramAddressSync(
-        [Buffer.from("results"), badProposalId.toArrayLike(Buffer, "le", 8)],
+        [Buffer.from("results"), spacePDA.toBuffer(), badProposalId.toArrayLike(Buffer, "le", 8)],
         program.programId
       );
 
       try {
         await program.methods
           .createProposal(
-            badProposalId,
             "Bad proposal",
             ["Only one option"],
-            tokenMint,
-            PROOF_THRESHOLD,
-            new BN(5)
+            null,
+            new BN(5),
+            new BN(60),
+            null,
+            ZK_VERIFIER_PROGRAM_ID,
+            null,
+            { commitReveal: {} },
+            { onePerVoter: {} },
+            { plurality: {} }
           )
           .accounts({
             creator: creator.publicKey,
+            governanceSpace: spacePDA,
             proposal: badProposalPDA,
             results: badResultsPDA,
+            deposit: depositPDA(badProposalPDA),
+            creatorTokenAccount: null,
+            verifierRegistry: verifierRegistryPDA,
             systemProgram: SystemProgram.programId,
           })
           .signers([creator])
@@ -178,145 +508,948 @@ describe("psephos", () => {
         expect(error.message).to.include("TooFewOptions");
       }
     });
-  });
-
-  describe("cast_vote with real ZK proof", () => {
-    it("should verify real ZK proof and cast vote on-chain", async () => {
-      console.log("Casting vote with real ZK proof...");
-      console.log("  Proof size:", realProof.length, "bytes");
-      console.log("  Witness size:", realWitness.length, "bytes");
-      console.log("  Nullifier:", KNOWN_NULLIFIER.toString('hex'));
-      console.log("  Commitment:", KNOWN_COMMITMENT.toString('hex'));
-
-      // Request more compute units for ZK verification (~500k CUs needed)
-      const computeBudgetIx = ComputeBudgetProgram.setComputeUnitLimit({
-        units: 1_000_000,
-      });
-
-      const tx = await program.methods
-        .castVote(
-          Array.from(KNOWN_NULLIFIER),
-          Array.from(KNOWN_COMMITMENT),
-          realProof,
-          realWitness
-        )
-        .accounts({
-          voter: voter1.publicKey,
-          proposal: proposalPDA,
-          voteRecord: voteRecordPDA,
-          voterTokenAccount: voter1TokenAccount,
-          zkVerifier: ZK_VERIFIER_PROGRAM_ID,
-          tokenProgram: TOKEN_PROGRAM_ID,
-          systemProgram: SystemProgram.programId,
-        })
-        .preInstructions([computeBudgetIx])
-        .signers([voter1])
-        .rpc();
-
-      console.log("Cast vote tx:", tx);
-
-      // Fetch and verify transaction logs
-      const txDetails = await provider.connection.getTransaction(tx, {
-        commitment: "confirmed",
-        maxSupportedTransactionVersion: 0,
-      });
-
-      const logs = txDetails?.meta?.logMessages || [];
-      console.log("\nTransaction logs:");
-      logs.forEach(log => console.log("  ", log));
 
-      // Verify ZK proof was verified - check for any verification message or success
-      // The CPI to verifier might not surface logs, so we check multiple indicators
-      const zkVerifiedLog = logs.find(log =>
-        log.includes("ZK proof cryptographically verified") ||
-        log.includes("ZK proof validated") ||
-        log.includes("Public inputs verified")
+    it("should fail to pin a verifier with the wrong public-input layout", async () => {
+      const badProposalId = await nextProposalId();
+      const [badProposalPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("proposal"), spacePDA.toBuffer(), badProposalId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [badResultsPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("results"), spacePDA.toBuffer(), badProposalId.toArrayLike(Buffer, "le", 8)],
+        program.programId
       );
-
-      // Alternative: If log isn't found, the transaction success itself proves verification
-      // (because the CPI would fail if verification failed)
-      if (!zkVerifiedLog) {
-        console.log("⚠️  ZK verification log not captured (CPI logs may not surface)");
-        console.log("✓ Transaction succeeded = verification passed (CPI would fail otherwise)");
-      } else {
-        console.log("✓ Found ZK verification log:", zkVerifiedLog);
-      }
-
-      // Verify vote record
-      const voteRecord = await program.account.voteRecord.fetch(voteRecordPDA);
-      assert.deepEqual(Array.from(voteRecord.nullifier), Array.from(KNOWN_NULLIFIER));
-      assert.deepEqual(Array.from(voteRecord.voteCommitment), Array.from(KNOWN_COMMITMENT));
-      assert.equal(voteRecord.isRevealed, false);
-
-      // Verify vote count
-      const proposal = await program.account.proposal.fetch(proposalPDA);
-      assert.equal(proposal.voteCount.toString(), "1");
-
-      console.log("\n✓ Real ZK proof verification successful!");
-    });
-
-    it("should prevent double voting with same nullifier", async () => {
-      const computeBudgetIx = ComputeBudgetProgram.setComputeUnitLimit({
-        units: 1_000_000,
-      });
 
       try {
+        // Token-account proposal pinning the snapshot circuit's verifier
         await program.methods
-          .castVote(
-            Array.from(KNOWN_NULLIFIER),
-            Array.from(KNOWN_COMMITMENT),
-            realProof,
-            realWitness
+          .createProposal(
+            "Bad verifier",
+            ["Yes", "No"],
+            null,
+            new BN(5),
+            new BN(60),
+            null,
+            SNAPSHOT_VERIFIER_PROGRAM_ID,
+            null,
+            { commitReveal: {} },
+            { onePerVoter: {} },
+            { plurality: {} }
           )
           .accounts({
-            voter: voter1.publicKey,
-            proposal: proposalPDA,
-            voteRecord: voteRecordPDA,
-            voterTokenAccount: voter1TokenAccount,
-            zkVerifier: ZK_VERIFIER_PROGRAM_ID,
-            tokenProgram: TOKEN_PROGRAM_ID,
+            creator: creator.publicKey,
+            governanceSpace: spacePDA,
+            proposal: badProposalPDA,
+            results: badResultsPDA,
+            deposit: depositPDA(badProposalPDA),
+            creatorTokenAccount: null,
+            verifierRegistry: verifierRegistryPDA,
             systemProgram: SystemProgram.programId,
           })
-          .preInstructions([computeBudgetIx])
-          .signers([voter1])
+          .signers([creator])
           .rpc();
 
-        assert.fail("Should have thrown an error for duplicate nullifier");
+        assert.fail("Should have thrown an error");
       } catch (error) {
-        expect(error.message).to.satisfy(
-          (msg: string) => msg.includes("already in use") || msg.includes("custom program error")
-        );
+        expect(error.message).to.include("VerifierLayoutMismatch");
       }
     });
-  });
 
-  describe("reveal_vote", () => {
-    it("should fail to reveal before voting ends", async () => {
-      const voterSecret = Buffer.alloc(32);
-      voterSecret[0] = 0x42;
+    it("should fail to pin a reveal verifier with the wrong public-input layout", async () => {
+      const badProposalId = await nextProposalId();
+      const [badProposalPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("proposal"), spacePDA.toBuffer(), badProposalId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [badResultsPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("results"), spacePDA.toBuffer(), badProposalId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
 
       try {
+        // The voting circuit's verifier pinned for proof-backed reveals
         await program.methods
-          .revealVote(1, Array.from(voterSecret)) // vote_choice = 1 matches Prover.toml
+          .createProposal(
+            "Bad reveal verifier",
+            ["Yes", "No"],
+            null,
+            new BN(5),
+            new BN(60),
+            null,
+            ZK_VERIFIER_PROGRAM_ID,
+            ZK_VERIFIER_PROGRAM_ID,
+            { commitReveal: {} },
+            { onePerVoter: {} },
+            { plurality: {} }
+          )
           .accounts({
-            revealer: voter1.publicKey,
-            proposal: proposalPDA,
-            voteRecord: voteRecordPDA,
-            results: resultsPDA,
+            creator: creator.publicKey,
+            governanceSpace: spacePDA,
+            proposal: badProposalPDA,
+            results: badResultsPDA,
+            deposit: depositPDA(badProposalPDA),
+            creatorTokenAccount: null,
+            verifierRegistry: verifierRegistryPDA,
+            systemProgram: SystemProgram.programId,
           })
-          .signers([voter1])
+          .signers([creator])
           .rpc();
 
-        assert.fail("Should have thrown VotingNotEnded error");
+        assert.fail("Should have thrown an error");
       } catch (error) {
-        expect(error.message).to.include("VotingNotEnded");
+        expect(error.message).to.include("VerifierLayoutMismatch");
       }
     });
 
-    it("should reveal vote after voting period ends", async () => {
+    it("should fail to pin a commit-reveal verifier for encrypted ballots", async () => {
+      const badProposalId = await nextProposalId();
+      const [badProposalPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("proposal"), spacePDA.toBuffer(), badProposalId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [badResultsPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("results"), spacePDA.toBuffer(), badProposalId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .createProposal(
+            "Bad encrypted verifier",
+            ["Yes", "No"],
+            null,
+            new BN(5),
+            new BN(60),
+            null,
+            ZK_VERIFIER_PROGRAM_ID,
+            null,
+            { encrypted: {} },
+            { onePerVoter: {} },
+            { plurality: {} }
+          )
+          .accounts({
+            creator: creator.publicKey,
+            governanceSpace: spacePDA,
+            proposal: badProposalPDA,
+            results: badResultsPDA,
+            deposit: depositPDA(badProposalPDA),
+            creatorTokenAccount: null,
+            verifierRegistry: verifierRegistryPDA,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([creator])
+          .rpc();
+
+        assert.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.message).to.include("VerifierLayoutMismatch");
+      }
+    });
+
+    it("should reject unsupported weighting and voting method combinations", async () => {
+      const badProposalId = await nextProposalId();
+      const [badProposalPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("proposal"), spacePDA.toBuffer(), badProposalId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [badResultsPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("results"), spacePDA.toBuffer(), badProposalId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      const tokenWeighted = { tokenWeighted: { unit: new BN(1_000), cap: null } };
+      for (const [ballotMode, weighting, votingMethod, expected] of [
+        [{ encrypted: {} }, { tokenWeighted: { unit: new BN(0), cap: null } }, { plurality: {} }, "InvalidVoteWeighting"],
+        [{ encrypted: {} }, tokenWeighted, { plurality: {} }, "WeightingUnsupported"],
+        // Without a snapshot one token balance could weight many voter secrets
+        [{ commitReveal: {} }, tokenWeighted, { plurality: {} }, "WeightingNeedsSnapshot"],
+        [{ commitReveal: {} }, tokenWeighted, { approval: {} }, "WeightingNeedsSnapshot"],
+        // Quadratic voting needs a token weight to take the root of
+        [{ commitReveal: {} }, { onePerVoter: {} }, { quadratic: {} }, "InvalidVotingMethod"],
+        // ...proven against a snapshot rather than read from a token account
+        [{ commitReveal: {} }, tokenWeighted, { quadratic: {} }, "InvalidVotingMethod"],
+        // Approval ballots are opened at reveal, so they need commit-reveal
+        [{ encrypted: {} }, { onePerVoter: {} }, { approval: {} }, "InvalidVotingMethod"],
+        [{ encrypted: {} }, { onePerVoter: {} }, { rankedChoice: {} }, "InvalidVotingMethod"],
+        // STV must leave at least one of the two options unelected
+        [{ commitReveal: {} }, { onePerVoter: {} }, { singleTransferableVote: { seats: 2 } }, "InvalidVotingMethod"],
+        [{ commitReveal: {} }, { onePerVoter: {} }, { singleTransferableVote: { seats: 0 } }, "InvalidVotingMethod"],
+      ] as const) {
+        try {
+          await program.methods
+            .createProposal(
+              "Unsupported weighting",
+              ["Yes", "No"],
+              null,
+              new BN(5),
+              new BN(60),
+              null,
+              ZK_VERIFIER_PROGRAM_ID,
+              null,
+              ballotMode,
+              weighting,
+              votingMethod
+            )
+            .accounts({
+              creator: creator.publicKey,
+              governanceSpace: spacePDA,
+              proposal: badProposalPDA,
+              results: badResultsPDA,
+              deposit: depositPDA(badProposalPDA),
+              creatorTokenAccount: null,
+              verifierRegistry: verifierRegistryPDA,
+              systemProgram: SystemProgram.programId,
+            })
+            .signers([creator])
+            .rpc();
+
+          assert.fail("Should have thrown an error");
+        } catch (error) {
+          expect(error.message).to.include(expected);
+        }
+      }
+    });
+  });
+
+  describe("scheduled proposals", () => {
+    let scheduledProposalId: BN;
+    let scheduledProposalPDA: PublicKey;
+    let scheduledResultsPDA: PublicKey;
+
+    before(async () => {
+      scheduledProposalId = await nextProposalId();
+      [scheduledProposalPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("proposal"), spacePDA.toBuffer(), scheduledProposalId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      [scheduledResultsPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("results"), spacePDA.toBuffer(), scheduledProposalId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+    });
+
+    it("should reject a start time in the past", async () => {
+      try {
+        await program.methods
+          .createProposal(
+            "Scheduled Voting Test",
+            ["Yes", "No"],
+            new BN(1),
+            new BN(60),
+            new BN(60),
+            null,
+            ZK_VERIFIER_PROGRAM_ID,
+            null,
+            { commitReveal: {} },
+            { onePerVoter: {} },
+            { plurality: {} }
+          )
+          .accounts({
+            creator: creator.publicKey,
+            governanceSpace: spacePDA,
+            proposal: scheduledProposalPDA,
+            results: scheduledResultsPDA,
+            deposit: depositPDA(scheduledProposalPDA),
+            creatorTokenAccount: null,
+            verifierRegistry: verifierRegistryPDA,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([creator])
+          .rpc();
+
+        assert.fail("Should have thrown StartTimeInPast error");
+      } catch (error) {
+        expect(error.message).to.include("StartTimeInPast");
+      }
+    });
+
+    it("should create a draft proposal that opens later", async () => {
+      const startTime = new BN(Math.floor(Date.now() / 1000) + 3600);
+
+      await program.methods
+        .createProposal(
+          "Scheduled Voting Test",
+          ["Yes", "No"],
+          startTime,
+          new BN(60),
+          new BN(60),
+          null,
+          ZK_VERIFIER_PROGRAM_ID,
+          null,
+          { commitReveal: {} },
+          { onePerVoter: {} },
+          { plurality: {} }
+        )
+        .accounts({
+          creator: creator.publicKey,
+          governanceSpace: spacePDA,
+          proposal: scheduledProposalPDA,
+          results: scheduledResultsPDA,
+          deposit: depositPDA(scheduledProposalPDA),
+          creatorTokenAccount: null,
+          verifierRegistry: verifierRegistryPDA,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([creator])
+        .rpc();
+
+      const proposal = await program.account.proposal.fetch(scheduledProposalPDA);
+      assert.deepEqual(proposal.status, { draft: {} });
+      assert.equal(proposal.startTime.toString(), startTime.toString());
+      assert.equal(proposal.endTime.toString(), startTime.addn(60).toString());
+    });
+
+    it("should reject votes before the start time", async () => {
+      const [scheduledVoteRecordPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("vote"), scheduledProposalPDA.toBuffer(), KNOWN_NULLIFIER],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .castVote(
+            Array.from(KNOWN_NULLIFIER),
+            Array.from(KNOWN_COMMITMENT),
+            realProof,
+            realWitness
+          )
+          .accounts({
+            voter: voter1.publicKey,
+            proposal: scheduledProposalPDA,
+            voteRecord: scheduledVoteRecordPDA,
+            voterTokenAccount: voter1TokenAccount,
+            zkVerifier: ZK_VERIFIER_PROGRAM_ID,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([voter1])
+          .rpc();
+
+        assert.fail("Should have thrown VotingNotStarted error");
+      } catch (error) {
+        expect(error.message).to.include("VotingNotStarted");
+      }
+    });
+
+    it("should let the creator amend the proposal before any vote", async () => {
+      const startTime = new BN(Math.floor(Date.now() / 1000) + 7200);
+
+      await program.methods
+        .amendProposal(
+          "Amended Voting Test",
+          ["Yes", "No", "Abstain"],
+          startTime,
+          new BN(120),
+          new BN(30)
+        )
+        .accounts({
+          creator: creator.publicKey,
+          governanceSpace: spacePDA,
+          proposal: scheduledProposalPDA,
+          results: scheduledResultsPDA,
+        })
+        .signers([creator])
+        .rpc();
+
+      const proposal = await program.account.proposal.fetch(scheduledProposalPDA);
+      assert.equal(proposal.title, "Amended Voting Test");
+      assert.equal(proposal.options.length, 3);
+      assert.deepEqual(proposal.status, { draft: {} });
+      assert.equal(proposal.endTime.toString(), startTime.addn(120).toString());
+      assert.equal(proposal.revealEndTime.toString(), startTime.addn(150).toString());
+
+      const results = await program.account.proposalResults.fetch(scheduledResultsPDA);
+      assert.equal(results.tallies.length, 3);
+    });
+
+    it("should fall back to the space's default periods when amending", async () => {
+      const startTime = new BN(Math.floor(Date.now() / 1000) + 7200);
+
+      await program.methods
+        .amendProposal("Amended Voting Test", ["Yes", "No", "Abstain"], startTime, null, null)
+        .accounts({
+          creator: creator.publicKey,
+          governanceSpace: spacePDA,
+          proposal: scheduledProposalPDA,
+          results: scheduledResultsPDA,
+        })
+        .signers([creator])
+        .rpc();
+
+      const { config } = await program.account.governanceSpace.fetch(spacePDA);
+      const proposal = await program.account.proposal.fetch(scheduledProposalPDA);
+      const endTime = startTime.add(config.votingPeriodSeconds);
+      assert.equal(proposal.endTime.toString(), endTime.toString());
+      assert.equal(
+        proposal.revealEndTime.toString(),
+        endTime.add(config.revealPeriodSeconds).toString()
+      );
+    });
+
+    it("should reject amendments from anyone but the creator", async () => {
+      try {
+        await program.methods
+          .amendProposal("Hijacked", ["A", "B"], null, new BN(60), new BN(60))
+          .accounts({
+            creator: voter1.publicKey,
+            governanceSpace: spacePDA,
+            proposal: scheduledProposalPDA,
+            results: scheduledResultsPDA,
+          })
+          .signers([voter1])
+          .rpc();
+
+        assert.fail("Should have thrown Unauthorized error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+
+    it("should cancel the proposal and close its accounts", async () => {
+      const transfer = SystemProgram.transfer({
+        fromPubkey: treasuryPDA,
+        toPubkey: creator.publicKey,
+        lamports: TREASURY_PAYOUT,
+      });
+      const attachedPDA = transactionPDA(scheduledProposalPDA, 0, 0);
+      await program.methods
+        .insertTransaction(0, {
+          programId: transfer.programId,
+          accounts: transfer.keys.map(key => ({
+            pubkey: key.pubkey,
+            isSigner: key.isSigner,
+            isWritable: key.isWritable,
+          })),
+          data: transfer.data,
+        })
+        .accounts({
+          creator: creator.publicKey,
+          proposal: scheduledProposalPDA,
+          proposalTransaction: attachedPDA,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([creator])
+        .rpc();
+
+      const cancel = () =>
+        program.methods
+          .cancelProposal()
+          .accounts({
+            authority: creator.publicKey,
+            creator: creator.publicKey,
+            governanceSpace: spacePDA,
+            proposal: scheduledProposalPDA,
+            results: scheduledResultsPDA,
+            encryptedTally: null,
+            rankedBallots: null,
+            deposit: depositPDA(scheduledProposalPDA),
+            treasury: treasuryPDA,
+          })
+          .signers([creator]);
+
+      // The attached transaction's rent would be stranded
+      try {
+        await cancel().rpc();
+        assert.fail("Should have thrown MissingProposalTransactions error");
+      } catch (error) {
+        expect(error.message).to.include("MissingProposalTransactions");
+      }
+
+      await cancel()
+        .remainingAccounts([{ pubkey: attachedPDA, isSigner: false, isWritable: true }])
+        .rpc();
+
+      assert.isNull(await provider.connection.getAccountInfo(attachedPDA));
+      assert.isNull(await provider.connection.getAccountInfo(scheduledProposalPDA));
+      assert.isNull(await provider.connection.getAccountInfo(scheduledResultsPDA));
+      assert.isNull(await provider.connection.getAccountInfo(depositPDA(scheduledProposalPDA)));
+    });
+
+    it("should slash the deposit when an admin cancels a proposal as spam", async () => {
+      const spamProposalId = await nextProposalId();
+      const [spamProposalPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("proposal"), spacePDA.toBuffer(), spamProposalId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [spamResultsPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("results"), spacePDA.toBuffer(), spamProposalId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      await program.methods
+        .createProposal("Spam", ["A", "B"], null, null, null, null, ZK_VERIFIER_PROGRAM_ID, null, { commitReveal: {} }, { onePerVoter: {} }, { plurality: {} })
+        .accounts({
+          creator: voter1.publicKey,
+          governanceSpace: spacePDA,
+          proposal: spamProposalPDA,
+          results: spamResultsPDA,
+          deposit: depositPDA(spamProposalPDA),
+          creatorTokenAccount: null,
+          verifierRegistry: verifierRegistryPDA,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([voter1])
+        .rpc();
+
+      const treasuryBefore = await provider.connection.getBalance(treasuryPDA);
+      await program.methods
+        .cancelProposal()
+        .accounts({
+          authority: creator.publicKey,
+          creator: voter1.publicKey,
+          governanceSpace: spacePDA,
+          proposal: spamProposalPDA,
+          results: spamResultsPDA,
+          encryptedTally: null,
+          rankedBallots: null,
+          deposit: depositPDA(spamProposalPDA),
+          treasury: treasuryPDA,
+        })
+        .signers([creator])
+        .rpc();
+      const treasuryAfter = await provider.connection.getBalance(treasuryPDA);
+
+      assert.isNull(await provider.connection.getAccountInfo(spamProposalPDA));
+      assert.isAbove(treasuryAfter - treasuryBefore, PROPOSAL_DEPOSIT.toNumber() - 1);
+    });
+  });
+
+  describe("cast_vote with real ZK proof", () => {
+    it("should verify real ZK proof and cast vote on-chain", async () => {
+      console.log("Casting vote with real ZK proof...");
+      console.log("  Proof size:", realProof.length, "bytes");
+      console.log("  Witness size:", realWitness.length, "bytes");
+      console.log("  Nullifier:", KNOWN_NULLIFIER.toString('hex'));
+      console.log("  Commitment:", KNOWN_COMMITMENT.toString('hex'));
+
+      // Request more compute units for ZK verification (~500k CUs needed)
+      const computeBudgetIx = ComputeBudgetProgram.setComputeUnitLimit({
+        units: 1_000_000,
+      });
+
+      const tx = await program.methods
+        .castVote(
+          Array.from(KNOWN_NULLIFIER),
+          Array.from(KNOWN_COMMITMENT),
+          realProof,
+          realWitness
+        )
+        .accounts({
+          voter: voter1.publicKey,
+          proposal: proposalPDA,
+          voteRecord: voteRecordPDA,
+          voterTokenAccount: voter1TokenAccount,
+          zkVerifier: ZK_VERIFIER_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([computeBudgetIx])
+        .signers([voter1])
+        .rpc();
+
+      console.log("Cast vote tx:", tx);
+
+      // Fetch and verify transaction logs
+      const txDetails = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+
+      const logs = txDetails?.meta?.logMessages || [];
+      console.log("\nTransaction logs:");
+      logs.forEach(log => console.log("  ", log));
+
+      // Verify ZK proof was verified - check for any verification message or success
+      // The CPI to verifier might not surface logs, so we check multiple indicators
+      const zkVerifiedLog = logs.find(log =>
+        log.includes("ZK proof cryptographically verified") ||
+        log.includes("ZK proof validated") ||
+        log.includes("Public inputs verified")
+      );
+
+      // Alternative: If log isn't found, the transaction success itself proves verification
+      // (because the CPI would fail if verification failed)
+      if (!zkVerifiedLog) {
+        console.log("⚠️  ZK verification log not captured (CPI logs may not surface)");
+        console.log("✓ Transaction succeeded = verification passed (CPI would fail otherwise)");
+      } else {
+        console.log("✓ Found ZK verification log:", zkVerifiedLog);
+      }
+
+      // Verify the typed VoteCast event
+      const eventParser = new anchor.EventParser(program.programId, program.coder);
+      const events = Array.from(eventParser.parseLogs(logs));
+      const voteCast = events.find(event => event.name === "voteCast");
+      assert.isDefined(voteCast, "VoteCast event should be emitted");
+      assert.deepEqual(Array.from(voteCast.data.nullifier as number[]), Array.from(KNOWN_NULLIFIER));
+      assert.deepEqual(Array.from(voteCast.data.voteCommitment as number[]), Array.from(KNOWN_COMMITMENT));
+
+      // Verify vote record
+      const voteRecord = await program.account.voteRecord.fetch(voteRecordPDA);
+      assert.deepEqual(Array.from(voteRecord.nullifier), Array.from(KNOWN_NULLIFIER));
+      assert.deepEqual(Array.from(voteRecord.voteCommitment), Array.from(KNOWN_COMMITMENT));
+      assert.equal(voteRecord.isRevealed, false);
+      assert.equal(voteRecord.weight.toString(), "1");
+
+      // Verify vote count
+      const proposal = await program.account.proposal.fetch(proposalPDA);
+      assert.equal(proposal.voteCount.toString(), "1");
+      assert.equal(proposal.totalWeight.toString(), "1");
+
+      console.log("\n✓ Real ZK proof verification successful!");
+    });
+
+    it("should prevent double voting with same nullifier", async () => {
+      const computeBudgetIx = ComputeBudgetProgram.setComputeUnitLimit({
+        units: 1_000_000,
+      });
+
+      try {
+        await program.methods
+          .castVote(
+            Array.from(KNOWN_NULLIFIER),
+            Array.from(KNOWN_COMMITMENT),
+            realProof,
+            realWitness
+          )
+          .accounts({
+            voter: voter1.publicKey,
+            proposal: proposalPDA,
+            voteRecord: voteRecordPDA,
+            voterTokenAccount: voter1TokenAccount,
+            zkVerifier: ZK_VERIFIER_PROGRAM_ID,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .preInstructions([computeBudgetIx])
+          .signers([voter1])
+          .rpc();
+
+        assert.fail("Should have thrown an error for duplicate nullifier");
+      } catch (error) {
+        expect(error.message).to.satisfy(
+          (msg: string) => msg.includes("already in use") || msg.includes("custom program error")
+        );
+      }
+    });
+
+    it("should reject the proof on the same proposal ID in another space", async () => {
+      // The proof's public witness is bound to the test space's first proposal
+      assert.deepEqual(realWitness.subarray(44, 76), proposalDomain(proposalPDA));
+
+      // The gated space's first proposal shares ID 1 with the proof's proposal
+      const [gatedSpacePDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("space"), voter1.publicKey.toBuffer(), Buffer.from("gated")],
+        program.programId
+      );
+      const [otherProposalPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("proposal"), gatedSpacePDA.toBuffer(), PROOF_PROPOSAL_ID.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [otherResultsPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("results"), gatedSpacePDA.toBuffer(), PROOF_PROPOSAL_ID.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      await program.methods
+        .createProposal("Same ID", ["Yes", "No"], null, null, null, null, ZK_VERIFIER_PROGRAM_ID, null, { commitReveal: {} }, { onePerVoter: {} }, { plurality: {} })
+        .accounts({
+          creator: voter1.publicKey,
+          governanceSpace: gatedSpacePDA,
+          proposal: otherProposalPDA,
+          results: otherResultsPDA,
+          deposit: depositPDA(otherProposalPDA),
+          creatorTokenAccount: null,
+          verifierRegistry: verifierRegistryPDA,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([voter1])
+        .rpc();
+
+      const [otherVoteRecordPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("vote"), otherProposalPDA.toBuffer(), KNOWN_NULLIFIER],
+        program.programId
+      );
+      try {
+        await program.methods
+          .castVote(
+            Array.from(KNOWN_NULLIFIER),
+            Array.from(KNOWN_COMMITMENT),
+            realProof,
+            realWitness
+          )
+          .accounts({
+            voter: voter1.publicKey,
+            proposal: otherProposalPDA,
+            voteRecord: otherVoteRecordPDA,
+            voterTokenAccount: voter1TokenAccount,
+            zkVerifier: ZK_VERIFIER_PROGRAM_ID,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([voter1])
+          .rpc();
+
+        assert.fail("Should have thrown ProposalDomainMismatch error");
+      } catch (error) {
+        expect(error.message).to.include("ProposalDomainMismatch");
+      }
+    });
+  });
+
+  describe("cast_vote with eligibility snapshot", () => {
+    let snapshotProposalId: BN;
+    const eligibilityRoot = Buffer.alloc(32, 0x07);
+    let snapshotProposalPDA: PublicKey;
+    let snapshotResultsPDA: PublicKey;
+
+    before(async () => {
+      snapshotProposalId = await nextProposalId();
+      [snapshotProposalPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("proposal"), spacePDA.toBuffer(), snapshotProposalId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      [snapshotResultsPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("results"), spacePDA.toBuffer(), snapshotProposalId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      await program.methods
+        .createProposal(
+          "Snapshot Voting Test",
+          ["Yes", "No"],
+          null,
+          new BN(60),
+          new BN(60),
+          Array.from(eligibilityRoot),
+          SNAPSHOT_VERIFIER_PROGRAM_ID,
+          null,
+          { commitReveal: {} },
+          { onePerVoter: {} },
+          { plurality: {} }
+        )
+        .accounts({
+          creator: creator.publicKey,
+          governanceSpace: spacePDA,
+          proposal: snapshotProposalPDA,
+          results: snapshotResultsPDA,
+          deposit: depositPDA(snapshotProposalPDA),
+          creatorTokenAccount: null,
+          verifierRegistry: verifierRegistryPDA,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([creator])
+        .rpc();
+    });
+
+    it("should store the eligibility root", async () => {
+      const proposal = await program.account.proposal.fetch(snapshotProposalPDA);
+      assert.deepEqual(Array.from(proposal.eligibilityRoot), Array.from(eligibilityRoot));
+    });
+
+    it("should reject a token-account proof without the root public input", async () => {
+      const [snapshotVoteRecordPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("vote"), snapshotProposalPDA.toBuffer(), KNOWN_NULLIFIER],
+        program.programId
+      );
+
+      try {
+        // No voter token account or token program: eligibility comes from the proof
+        await program.methods
+          .castVote(
+            Array.from(KNOWN_NULLIFIER),
+            Array.from(KNOWN_COMMITMENT),
+            realProof,
+            realWitness
+          )
+          .accounts({
+            voter: voter1.publicKey,
+            proposal: snapshotProposalPDA,
+            voteRecord: snapshotVoteRecordPDA,
+            voterTokenAccount: null,
+            zkVerifier: SNAPSHOT_VERIFIER_PROGRAM_ID,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([voter1])
+          .rpc();
+
+        assert.fail("Should have thrown InvalidPublicWitness error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidPublicWitness");
+      }
+    });
+
+    it("should create and fund a relayer pool", async () => {
+      const [relayerPoolPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("relayer_pool"), snapshotProposalPDA.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .initRelayerPool(new BN(5000))
+        .accounts({
+          creator: creator.publicKey,
+          proposal: snapshotProposalPDA,
+          relayerPool: relayerPoolPDA,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([creator])
+        .rpc();
+
+      await program.methods
+        .fundRelayerPool(new BN(LAMPORTS_PER_SOL / 10))
+        .accounts({
+          funder: creator.publicKey,
+          relayerPool: relayerPoolPDA,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([creator])
+        .rpc();
+
+      const pool = await program.account.relayerPool.fetch(relayerPoolPDA);
+      assert.equal(pool.feePerVote.toString(), "5000");
+      assert.equal(pool.totalPaid.toString(), "0");
+      assert.ok(pool.creator.equals(creator.publicKey));
+    });
+
+    it("should keep the relayer pool open until the proposal is settled", async () => {
+      const [relayerPoolPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("relayer_pool"), snapshotProposalPDA.toBuffer()],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .closeRelayerPool()
+          .accounts({
+            creator: creator.publicKey,
+            relayerPool: relayerPoolPDA,
+            proposal: snapshotProposalPDA,
+          })
+          .signers([creator])
+          .rpc();
+
+        assert.fail("Should have thrown ProposalNotSettled error");
+      } catch (error) {
+        expect(error.message).to.include("ProposalNotSettled");
+      }
+    });
+
+    it("should validate relayed proofs without a voter signature", async () => {
+      const relayer = creator; // any funded key can relay
+      const [relayerPoolPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("relayer_pool"), snapshotProposalPDA.toBuffer()],
+        program.programId
+      );
+      const [snapshotVoteRecordPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("vote"), snapshotProposalPDA.toBuffer(), KNOWN_NULLIFIER],
+        program.programId
+      );
+
+      try {
+        // The proof is checked before any state changes; this witness lacks the root
+        await program.methods
+          .castVoteRelayed(
+            Array.from(KNOWN_NULLIFIER),
+            Array.from(KNOWN_COMMITMENT),
+            realProof,
+            realWitness
+          )
+          .accounts({
+            relayer: relayer.publicKey,
+            proposal: snapshotProposalPDA,
+            voteRecord: snapshotVoteRecordPDA,
+            relayerPool: relayerPoolPDA,
+            zkVerifier: SNAPSHOT_VERIFIER_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([relayer])
+          .rpc();
+
+        assert.fail("Should have thrown InvalidPublicWitness error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidPublicWitness");
+      }
+    });
+  });
+
+  describe("reveal_vote", () => {
+    it("should fail to reveal before voting ends", async () => {
+      const voterSecret = Buffer.alloc(32);
+      voterSecret[0] = 0x42;
+
+      try {
+        await program.methods
+          .revealVote(1, Array.from(voterSecret)) // vote_choice = 1 matches Prover.toml
+          .accounts({
+            revealer: voter1.publicKey,
+            proposal: proposalPDA,
+            voteRecord: voteRecordPDA,
+            results: resultsPDA,
+          })
+          .signers([voter1])
+          .rpc();
+
+        assert.fail("Should have thrown VotingNotEnded error");
+      } catch (error) {
+        expect(error.message).to.include("VotingNotEnded");
+      }
+    });
+
+    it("should reject a reveal that does not open the commitment", async () => {
       console.log("Waiting for voting period to end...");
       await new Promise(resolve => setTimeout(resolve, 6000));
 
+      const voterSecret = Buffer.alloc(32);
+      voterSecret.writeBigUInt64BE(BigInt(12345), 24);
+
+      try {
+        // Committed choice is 1; claiming 0 must not move the tally
+        await program.methods
+          .revealVote(0, Array.from(voterSecret))
+          .accounts({
+            revealer: voter1.publicKey,
+            proposal: proposalPDA,
+            voteRecord: voteRecordPDA,
+            results: resultsPDA,
+          })
+          .signers([voter1])
+          .rpc();
+
+        assert.fail("Should have thrown InvalidReveal error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidReveal");
+      }
+    });
+
+    it("should reject a proof reveal whose witness does not match the commitment", async () => {
+      // Header: input count, padding, element count (3 public inputs)
+      const header = Buffer.from([0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 3]);
+      const choiceField = Buffer.alloc(32);
+      choiceField[31] = 1;
+      const wrongCommitment = Buffer.alloc(32, 0x11);
+      const witness = Buffer.concat([header, proposalDomain(proposalPDA), choiceField, wrongCommitment]);
+
+      try {
+        await program.methods
+          .revealVoteWithProof(1, Buffer.alloc(388), witness)
+          .accounts({
+            revealer: voter1.publicKey,
+            proposal: proposalPDA,
+            voteRecord: voteRecordPDA,
+            results: resultsPDA,
+            zkVerifier: REVEAL_VERIFIER_PROGRAM_ID,
+          })
+          .signers([voter1])
+          .rpc();
+
+        assert.fail("Should have thrown CommitmentMismatch error");
+      } catch (error) {
+        expect(error.message).to.include("CommitmentMismatch");
+      }
+    });
+
+    it("should reveal vote after voting period ends", async () => {
       // voter_secret = 12345 from Prover.toml, stored as 32-byte big-endian
       const voterSecret = Buffer.alloc(32);
       voterSecret.writeBigUInt64BE(BigInt(12345), 24);
@@ -341,6 +1474,9 @@ describe("psephos", () => {
 
       const results = await program.account.proposalResults.fetch(resultsPDA);
       assert.equal(results.tallies[1].toString(), "1"); // "No" has 1 vote
+      assert.equal(results.countedWeight.toString(), "1");
+      // Only STV tallies elect options
+      assert.deepEqual(results.elected, []);
     });
 
     it("should fail to reveal same vote twice", async () => {
@@ -366,7 +1502,386 @@ describe("psephos", () => {
     });
   });
 
+  describe("ranked-choice voting", () => {
+    // A space of its own fixes the ranked proposals' addresses, which the
+    // ballot commitments below open against
+    const RANKED_SPACE_NAME = "ranked-tests";
+    const [rankedSpacePDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("space"), creator.publicKey.toBuffer(), Buffer.from(RANKED_SPACE_NAME)],
+      program.programId
+    );
+    const [rankedTreasuryPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("treasury"), rankedSpacePDA.toBuffer()],
+      program.programId
+    );
+
+    // Verifying key whose every point is a generator and whose public input
+    // terms vanish, so the proof (G1, G2, -G1) holds for any public witness
+    const TRAPDOOR_KEY_ID = new BN(2);
+    const [trapdoorKeyPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("verifying_key"), TRAPDOOR_KEY_ID.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const G1 = Buffer.from(
+      "0000000000000000000000000000000000000000000000000000000000000001" +
+      "0000000000000000000000000000000000000000000000000000000000000002",
+      "hex"
+    );
+    const NEG_G1 = Buffer.from(
+      "0000000000000000000000000000000000000000000000000000000000000001" +
+      "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45",
+      "hex"
+    );
+    const G2 = Buffer.from(
+      "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2" +
+      "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed" +
+      "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b" +
+      "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
+      "hex"
+    );
+    const trapdoorProof = Buffer.concat([G1, G2, NEG_G1]);
+
+    // Ballots of the space's first proposal; commitments are
+    // poseidon(packed ranking, secret, proposal_domain) for its address
+    const IRV_BALLOTS: [number, number[], string][] = [
+      [1, [0], "263660d1ebb03b6be61704a105932229c97c5bbde38932a5bda8946ca766e6ac"],
+      [2, [1, 2], "181b1ce78eb891b99f287a166ce664985ae0c26008a5d89c7a4ca5bc8432871a"],
+      [3, [2], "10a427f3d3a93549e36d61140e79e63eb634c31a1e67d7c571e72c28b8e872a4"],
+      [4, [0], "21c2159f82a54ebd59fac218d0ec2e994ea224b43528379c35c72893d818aef4"],
+      [5, [2], "1b8ed78da29795dbecafd790610a1510bb752dba236fc8fd18ee1053a60d5a21"],
+    ];
+
+    // Ballots of the second: five for A then B, interleaved with the rest
+    const STV_BALLOTS: [number, number[], string][] = [
+      [11, [0, 1], "0ffc107bc1f528025ca4bd1dfa7a350210351f8ae5d77bc5e1e3d3245219a633"],
+      [12, [1], "1dec1eeacde16d87a4ee7e6325211c3f34e06cb2ccc2caed069f049e0bdd5117"],
+      [13, [0, 1], "099c1bb8194eef77a77be11da4c3aabe6b50b70b261a382311a556adf7430d88"],
+      [14, [2], "2b3413ef6aac7ac940a64815de5ee2daea541b17170d8a553e1515f0f87b7a73"],
+      [15, [0, 1], "067fa3a6d7d9a8e207f59b0f500819cbd81d07c719e7720620dd6a6d9e65ba8f"],
+      [16, [3], "2ba6f47b5aca4831e38bd524345dd3f6016907b91328b3e0158b9b330b4b5d3d"],
+      [17, [0, 1], "2170e7c34eb018dc0e6ee6df4cda7631c22e1d719555bc2cc86c1901bcad705a"],
+      [18, [2], "29ad59634b65f44b62b5dad4ba364c23437994015bab317aa08640f14cfd36b3"],
+      [19, [0, 1], "05c2d68e76c1f63899bd0576340118ed0d13bc2d1ba538bd4c6c0d3c9bead707"],
+    ];
+
+    const field = (value: number) => {
+      const element = Buffer.alloc(32);
+      element.writeUInt32BE(value, 28);
+      return element;
+    };
+
+    const rankedPDAs = (id: BN) => {
+      const [proposal] = PublicKey.findProgramAddressSync(
+        [Buffer.from("proposal"), rankedSpacePDA.toBuffer(), id.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [results] = PublicKey.findProgramAddressSync(
+        [Buffer.from("results"), rankedSpacePDA.toBuffer(), id.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [rankedBallots] = PublicKey.findProgramAddressSync(
+        [Buffer.from("ranked_ballots"), proposal.toBuffer()],
+        program.programId
+      );
+      return { proposal, results, rankedBallots };
+    };
+
+    // Create a ranked proposal in the ranked space and its ballot buffer
+    const createRankedProposal = async (id: BN, options: string[], votingMethod: any) => {
+      const { proposal, results, rankedBallots } = rankedPDAs(id);
+      await program.methods
+        .createProposal(
+          "Ranked crank test",
+          options,
+          null,
+          new BN(20), // long enough to cast every ballot
+          null,
+          null,
+          trapdoorKeyPDA,
+          null,
+          { commitReveal: {} },
+          { onePerVoter: {} },
+          votingMethod
+        )
+        .accounts({
+          creator: creator.publicKey,
+          governanceSpace: rankedSpacePDA,
+          proposal,
+          results,
+          deposit: depositPDA(proposal),
+          creatorTokenAccount: null,
+          verifierRegistry: verifierRegistryPDA,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([creator])
+        .rpc();
+
+      await program.methods
+        .initRankedBallots()
+        .accounts({
+          payer: creator.publicKey,
+          proposal,
+          rankedBallots,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([creator])
+        .rpc();
+    };
+
+    // Cast every ballot, wait out the voting period and reveal them in order
+    // (the buffer keeps reveal order)
+    const castAndReveal = async (id: BN, numOptions: number, ballots: [number, number[], string][]) => {
+      const { proposal, results, rankedBallots } = rankedPDAs(id);
+      const header = Buffer.from([0, 0, 0, 5, 0, 0, 0, 0, 0, 0, 0, 5]);
+      const voteRecord = (nullifier: Buffer) =>
+        PublicKey.findProgramAddressSync(
+          [Buffer.from("vote"), proposal.toBuffer(), nullifier],
+          program.programId
+        )[0];
+
+      // The trapdoor key binds no nullifier to the secret; any unique one does
+      for (const [secret, , commitment] of ballots) {
+        const nullifier = field(secret);
+        const witness = Buffer.concat([
+          header,
+          field(PROOF_THRESHOLD.toNumber()),
+          proposalDomain(proposal),
+          Buffer.from(commitment, "hex"),
+          nullifier,
+          field(numOptions),
+        ]);
+        await program.methods
+          .castVote(Array.from(nullifier), Array.from(Buffer.from(commitment, "hex")), trapdoorProof, witness)
+          .accounts({
+            voter: voter1.publicKey,
+            proposal,
+            voteRecord: voteRecord(nullifier),
+            voterTokenAccount: voter1TokenAccount,
+            zkVerifier: trapdoorKeyPDA,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 1_000_000 })])
+          .signers([voter1])
+          .rpc();
+      }
+
+      const { endTime } = await program.account.proposal.fetch(proposal);
+      const untilEnd = endTime.toNumber() * 1000 - Date.now();
+      await new Promise(resolve => setTimeout(resolve, untilEnd + 2000));
+
+      for (const [secret, ranking] of ballots) {
+        await program.methods
+          .revealRankedVote(Buffer.from(ranking), Array.from(field(secret)))
+          .accounts({
+            revealer: voter1.publicKey,
+            proposal,
+            voteRecord: voteRecord(field(secret)),
+            results,
+            rankedBallots,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([voter1])
+          .rpc();
+      }
+    };
+
+    // Crank `tallyRound` until the tally completes, returning the number of
+    // transactions it took
+    const crankTally = async (id: BN, maxBallots: number) => {
+      const { proposal, results, rankedBallots } = rankedPDAs(id);
+      let transactions = 0;
+      while (!(await program.account.proposalResults.fetch(results)).tallyComplete) {
+        await program.methods
+          .tallyRound(maxBallots)
+          .accounts({ proposal, results, rankedBallots })
+          .rpc();
+        transactions += 1;
+      }
+      return transactions;
+    };
+
+    const finalizeRanked = async (id: BN) => {
+      const { proposal, results } = rankedPDAs(id);
+      await program.methods
+        .finalizeProposal()
+        .accounts({
+          proposal,
+          results,
+          encryptedTally: null,
+          tokenMint: null,
+          authority: creator.publicKey,
+          deposit: depositPDA(proposal),
+          creator: creator.publicKey,
+          treasury: rankedTreasuryPDA,
+        })
+        .signers([creator])
+        .rpc();
+      return program.account.proposalResults.fetch(results);
+    };
+
+    before(async () => {
+      await program.methods
+        .createSpace(RANKED_SPACE_NAME, [creator.publicKey], [], spaceConfig())
+        .accounts({
+          creator: creator.publicKey,
+          governanceSpace: rankedSpacePDA,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([creator])
+        .rpc();
+
+      await program.methods
+        .initVerifyingKey(
+          TRAPDOOR_KEY_ID,
+          Array.from(G1),
+          Array.from(G2),
+          Array.from(G2),
+          Array.from(G2),
+          // Constant term, then the five ranked public inputs
+          [Array.from(G1), ...Array(5).fill(Array(64).fill(0))],
+          null
+        )
+        .accounts({
+          admin: provider.wallet.publicKey,
+          verifierRegistry: verifierRegistryPDA,
+          verifyingKey: trapdoorKeyPDA,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .addVerifier(trapdoorKeyPDA, 1, { rankedEligibility: {} })
+        .accounts({ admin: provider.wallet.publicKey, verifierRegistry: verifierRegistryPDA })
+        .rpc();
+    });
+
+    it("should run an instant runoff over several crank transactions", async () => {
+      const id = new BN(1);
+      await createRankedProposal(id, ["A", "B", "C"], { rankedChoice: {} });
+      await castAndReveal(id, 3, IRV_BALLOTS);
+
+      // Two ballots per transaction: three for each of the two rounds
+      assert.equal(await crankTally(id, 2), 6);
+
+      // B is eliminated and its ballot carries C past A
+      const { results } = rankedPDAs(id);
+      const tallied = await program.account.proposalResults.fetch(results);
+      assert.equal(tallied.rounds.length, 2);
+      assert.deepEqual(tallied.rounds[0].tallies.map(t => t.toNumber()), [2, 1, 2]);
+      assert.equal(tallied.rounds[0].eliminated, 1);
+      assert.deepEqual(tallied.rounds[1].tallies.map(t => t.toNumber()), [2, 0, 3]);
+      assert.equal(tallied.eliminated, 0b10);
+
+      const finalized = await finalizeRanked(id);
+      assert.deepEqual(finalized.outcome, { passed: {} });
+      assert.equal(finalized.winningOption, 2);
+    });
+
+    it("should elect two seats by STV with a surplus passed on over several cranks", async () => {
+      const id = new BN(2);
+      await createRankedProposal(id, ["A", "B", "C", "D"], { singleTransferableVote: { seats: 2 } });
+
+      // Elected seats are not a winning option to execute for
+      const { proposal } = rankedPDAs(id);
+      try {
+        await program.methods
+          .insertTransaction(0, {
+            programId: SystemProgram.programId,
+            accounts: [],
+            data: Buffer.alloc(0),
+          })
+          .accounts({
+            creator: creator.publicKey,
+            proposal,
+            proposalTransaction: transactionPDA(proposal, 0, 0),
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([creator])
+          .rpc();
+
+        assert.fail("Should have thrown VotingMethodMismatch error");
+      } catch (error) {
+        expect(error.message).to.include("VotingMethodMismatch");
+      }
+
+      await castAndReveal(id, 4, STV_BALLOTS);
+
+      // Four ballots per transaction: three for each of the four rounds, so
+      // A's surplus reaches B over three transactions
+      assert.equal(await crankTally(id, 4), 12);
+
+      const { results } = rankedPDAs(id);
+      const tallied = await program.account.proposalResults.fetch(results);
+      // Droop quota of the first round's 9 votes, in units of 1e-9
+      assert.equal(tallied.quota.toString(), "3000000001");
+      assert.equal(tallied.rounds[0].elected, 0);
+      // B holds 1 + 5 * 2/5 votes, just short of the quota
+      assert.deepEqual(tallied.rounds[1].tallies.map(t => t.toNumber()), [0, 2, 2, 1]);
+      assert.equal(tallied.rounds[1].eliminated, 3);
+      assert.equal(tallied.rounds[2].eliminated, 2);
+      assert.equal(tallied.rounds[2].exhaustedWeight.toNumber(), 1);
+      // The surplus carries B past C's two first preferences
+      assert.deepEqual(Array.from(tallied.elected), [0, 1]);
+
+      // Passed for electing its seats, with no single winner
+      const finalized = await finalizeRanked(id);
+      assert.deepEqual(finalized.outcome, { passed: {} });
+      assert.isNull(finalized.winningOption);
+    });
+
+    it("should reject a ballot buffer for a plurality proposal", async () => {
+      const [rankedBallotsPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("ranked_ballots"), proposalPDA.toBuffer()],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .initRankedBallots()
+          .accounts({
+            payer: creator.publicKey,
+            proposal: proposalPDA,
+            rankedBallots: rankedBallotsPDA,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([creator])
+          .rpc();
+
+        assert.fail("Should have thrown VotingMethodMismatch error");
+      } catch (error) {
+        expect(error.message).to.include("VotingMethodMismatch");
+      }
+    });
+
+    it("should reject runoff rounds for a plurality proposal", async () => {
+      try {
+        await program.methods
+          .tallyRound(100)
+          .accounts({
+            proposal: proposalPDA,
+            results: resultsPDA,
+            rankedBallots: null,
+          })
+          .rpc();
+
+        assert.fail("Should have thrown VotingMethodMismatch error");
+      } catch (error) {
+        expect(error.message).to.include("VotingMethodMismatch");
+      }
+    });
+  });
+
   describe("finalize_proposal", () => {
+    it("should report the clock-derived status before finalizing", async () => {
+      // Stored status advanced to Revealing when the vote was revealed
+      const status = await program.methods
+        .proposalStatus()
+        .accounts({ proposal: proposalPDA })
+        .view();
+      assert.deepEqual(status, { revealing: {} });
+    });
+
     it("should fail to finalize if not creator", async () => {
       try {
         await program.methods
@@ -374,7 +1889,12 @@ describe("psephos", () => {
           .accounts({
             proposal: proposalPDA,
             results: resultsPDA,
+            encryptedTally: null,
+            tokenMint: null,
             authority: voter1.publicKey,
+            deposit: depositPDA(proposalPDA),
+            creator: creator.publicKey,
+            treasury: treasuryPDA,
           })
           .signers([voter1])
           .rpc();
@@ -391,7 +1911,12 @@ describe("psephos", () => {
         .accounts({
           proposal: proposalPDA,
           results: resultsPDA,
+          encryptedTally: null,
+          tokenMint: null,
           authority: creator.publicKey,
+          deposit: depositPDA(proposalPDA),
+          creator: creator.publicKey,
+          treasury: treasuryPDA,
         })
         .signers([creator])
         .rpc();
@@ -399,10 +1924,12 @@ describe("psephos", () => {
       console.log("Finalize proposal tx:", tx);
 
       const proposal = await program.account.proposal.fetch(proposalPDA);
-      assert.equal(proposal.isFinalized, true);
+      assert.deepEqual(proposal.status, { finalized: {} });
 
+      // The single revealed vote went to option 1
       const results = await program.account.proposalResults.fetch(resultsPDA);
-      assert.equal(results.isFinalized, true);
+      assert.deepEqual(results.outcome, { passed: {} });
+      assert.equal(results.winningOption, 1);
     });
 
     it("should fail to finalize already finalized proposal", async () => {
@@ -412,7 +1939,12 @@ describe("psephos", () => {
           .accounts({
             proposal: proposalPDA,
             results: resultsPDA,
+            encryptedTally: null,
+            tokenMint: null,
             authority: creator.publicKey,
+            deposit: depositPDA(proposalPDA),
+            creator: creator.publicKey,
+            treasury: treasuryPDA,
           })
           .signers([creator])
           .rpc();
@@ -422,5 +1954,179 @@ describe("psephos", () => {
         expect(error.message).to.include("ProposalFinalized");
       }
     });
+
+    it("should let anyone finalize for the bounty after the reveal window", async () => {
+      const crankProposalId = await nextProposalId();
+      const [crankProposalPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("proposal"), spacePDA.toBuffer(), crankProposalId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [crankResultsPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("results"), spacePDA.toBuffer(), crankProposalId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      await program.methods
+        .createProposal(
+          "Crank Finalize Test",
+          ["Yes", "No"],
+          null,
+          new BN(1),
+          new BN(1),
+          null,
+          ZK_VERIFIER_PROGRAM_ID,
+          null,
+          { commitReveal: {} },
+          { onePerVoter: {} },
+          { plurality: {} }
+        )
+        .accounts({
+          creator: creator.publicKey,
+          governanceSpace: spacePDA,
+          proposal: crankProposalPDA,
+          results: crankResultsPDA,
+          deposit: depositPDA(crankProposalPDA),
+          creatorTokenAccount: null,
+          verifierRegistry: verifierRegistryPDA,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([creator])
+        .rpc();
+
+      const bounty = new BN(10_000);
+      await program.methods
+        .fundFinalizeBounty(bounty)
+        .accounts({
+          funder: creator.publicKey,
+          proposal: crankProposalPDA,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([creator])
+        .rpc();
+
+      console.log("Waiting for the reveal window to close...");
+      await new Promise(resolve => setTimeout(resolve, 4000));
+
+      const balanceBefore = await provider.connection.getBalance(voter1.publicKey);
+      await program.methods
+        .finalizeProposal()
+        .accounts({
+          proposal: crankProposalPDA,
+          results: crankResultsPDA,
+          encryptedTally: null,
+          tokenMint: null,
+          authority: voter1.publicKey,
+          deposit: depositPDA(crankProposalPDA),
+          creator: creator.publicKey,
+          treasury: treasuryPDA,
+        })
+        .signers([voter1])
+        .rpc();
+      const balanceAfter = await provider.connection.getBalance(voter1.publicKey);
+
+      const proposal = await program.account.proposal.fetch(crankProposalPDA);
+      assert.deepEqual(proposal.status, { finalized: {} });
+      assert.equal(proposal.finalizeBounty.toString(), "0");
+      // No votes were revealed
+      const results = await program.account.proposalResults.fetch(crankResultsPDA);
+      assert.deepEqual(results.outcome, { tie: {} });
+      // Bounty minus the transaction fee
+      assert.isAbove(balanceAfter - balanceBefore + 5000, 0);
+    });
+
+    it("should execute the winning option's transaction signed by the treasury", async () => {
+      const transactionRent = (await provider.connection.getAccountInfo(transactionPDA(proposalPDA, 1, 0))).lamports;
+      const balanceBefore = await provider.connection.getBalance(creator.publicKey);
+      await program.methods
+        .executeProposal()
+        .accounts({
+          proposal: proposalPDA,
+          results: resultsPDA,
+          proposalTransaction: transactionPDA(proposalPDA, 1, 0),
+          creator: creator.publicKey,
+          treasury: treasuryPDA,
+        })
+        .remainingAccounts([
+          { pubkey: creator.publicKey, isSigner: false, isWritable: true },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ])
+        .rpc();
+      const balanceAfter = await provider.connection.getBalance(creator.publicKey);
+
+      // The payout, plus the rent of the executed transaction's account
+      assert.equal(balanceAfter - balanceBefore, TREASURY_PAYOUT + transactionRent);
+      assert.isNull(await provider.connection.getAccountInfo(transactionPDA(proposalPDA, 1, 0)));
+      const proposal = await program.account.proposal.fetch(proposalPDA);
+      assert.deepEqual(proposal.status, { executed: {} });
+    });
+
+    it("should only let the refund key close a vote record", async () => {
+      try {
+        await program.methods
+          .closeVoteRecord()
+          .accounts({
+            refundAuthority: creator.publicKey,
+            recipient: creator.publicKey,
+            voteRecord: voteRecordPDA,
+            proposal: proposalPDA,
+          })
+          .signers([creator])
+          .rpc();
+
+        assert.fail("Should have thrown Unauthorized error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+
+    it("should archive the finalized proposal and close its accounts", async () => {
+      const [archivePDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("archive"), spacePDA.toBuffer(), PROOF_PROPOSAL_ID.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      await program.methods
+        .archiveProposal()
+        .accounts({
+          creator: creator.publicKey,
+          proposal: proposalPDA,
+          results: resultsPDA,
+          deposit: depositPDA(proposalPDA),
+          encryptedTally: null,
+          rankedBallots: null,
+          relayerPool: null,
+          archive: archivePDA,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([creator])
+        .rpc();
+
+      assert.isNull(await provider.connection.getAccountInfo(proposalPDA));
+      assert.isNull(await provider.connection.getAccountInfo(resultsPDA));
+
+      const archive = await program.account.proposalArchive.fetch(archivePDA);
+      assert.equal(archive.proposalId.toString(), PROOF_PROPOSAL_ID.toString());
+      assert.deepEqual(archive.outcome, { passed: {} });
+      assert.equal(archive.winningOption, 1);
+      assert.equal(archive.tallies.length, 3);
+    });
+
+    it("should let the voter reclaim the vote record rent after archiving", async () => {
+      const recipient = Keypair.generate().publicKey;
+
+      await program.methods
+        .closeVoteRecord()
+        .accounts({
+          refundAuthority: voter1.publicKey,
+          recipient,
+          voteRecord: voteRecordPDA,
+          proposal: proposalPDA,
+        })
+        .signers([voter1])
+        .rpc();
+
+      assert.isNull(await provider.connection.getAccountInfo(voteRecordPDA));
+      assert.isAbove(await provider.connection.getBalance(recipient), 0);
+    });
   });
 });
@@ -43,7 +43,7 @@ function toFieldHex(value) {
  * Generate a ZK proof for a vote
  *
  * This endpoint uses a two-phase approach:
- * 1. Run nargo test to compute pedersen hashes (nullifier + commitment)
+ * 1. Run nargo to compute poseidon hashes (nullifier + commitment)
  * 2. Use those values to generate the full Gnark proof
  *
  * POST /generate-proof
@@ -96,8 +96,8 @@ fn main() {
     let proposal_id: Field = ${proposalIdHex};
     let vote_choice: u8 = ${voteChoice};
 
-    let nullifier = std::hash::pedersen_hash([voter_secret, proposal_id]);
-    let commitment = std::hash::pedersen_hash([vote_choice as Field, voter_secret, proposal_id]);
+    let nullifier = poseidon::poseidon::bn254::hash_2([voter_secret, proposal_id]);
+    let commitment = poseidon::poseidon::bn254::hash_3([vote_choice as Field, voter_secret, proposal_id]);
 
     std::println(f"NULLIFIER:{nullifier}");
     std::println(f"COMMITMENT:{commitment}");
@@ -115,6 +115,7 @@ name = "compute_hashes"
 type = "bin"
 authors = ["psephos"]
 [dependencies]
+poseidon = { tag = "v0.1.1", git = "https://github.com/noir-lang/poseidon" }
 `);
 
     // Write the compute script
@@ -409,7 +410,7 @@ function hexToBytes(hex) {
 
 /**
  * POST /compute-hashes
- * Compute ONLY the Pedersen hashes (nullifier and commitment)
+ * Compute ONLY the Poseidon hashes (nullifier and commitment)
  * Used by browser proving to get correct hash values
  * Body: { voterSecret, voteChoice, proposalId }
  */
@@ -527,6 +528,6 @@ app.listen(PORT, () => {
   console.log('');
   console.log('Endpoints:');
   console.log('  GET  /health - Check server status');
-  console.log('  POST /compute-hashes - Compute Pedersen hashes only');
+  console.log('  POST /compute-hashes - Compute Poseidon hashes only');
   console.log('  POST /generate-proof-with-hashes - Generate Gnark proof with pre-computed hashes');
 });
@@ -0,0 +1,50 @@
+#!/bin/bash
+# Rebuild the proof fixtures the Anchor tests verify for real
+#
+# Produces in circuits/target:
+#   psephos_circuits.proof / .pw  - proof and public witness for Prover.toml
+#   psephos_circuits.so           - Sunspot verifier loaded at genesis by Anchor.toml
+#
+# The proving key is only regenerated when the circuit changes, so the
+# verifier stays in step with the proof. Requires nargo and sunspot on PATH.
+# Run automatically by `anchor test`; run by hand after editing the circuit
+# or Prover.toml.
+
+set -e
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+PROJECT_DIR="$(dirname "$SCRIPT_DIR")"
+CIRCUIT_DIR="$PROJECT_DIR/circuits"
+NAME="psephos_circuits"
+TARGET="$CIRCUIT_DIR/target"
+
+for tool in nargo sunspot; do
+  if ! command -v "$tool" > /dev/null; then
+    echo "error: $tool not found on PATH" >&2
+    exit 1
+  fi
+done
+
+cd "$CIRCUIT_DIR"
+
+echo "=== Building $NAME fixtures ==="
+
+# Step 1: Compile the circuit and solve the witness from Prover.toml
+nargo compile
+nargo execute
+
+# Step 2: Compile to gnark constraints; set up keys only if they changed
+sunspot compile "$TARGET/$NAME.json"
+if [ ! -f "$TARGET/$NAME.pk" ] || [ "$TARGET/$NAME.ccs" -nt "$TARGET/$NAME.pk" ]; then
+  sunspot setup "$TARGET/$NAME.ccs"
+fi
+
+# Step 3: Prove and check the fixtures against the verifying key
+sunspot prove "$TARGET/$NAME.json" "$TARGET/$NAME.gz" "$TARGET/$NAME.ccs" "$TARGET/$NAME.pk"
+sunspot verify "$TARGET/$NAME.vk" "$TARGET/$NAME.proof" "$TARGET/$NAME.pw"
+
+# Step 4: Build the verifier program matching the verifying key
+sunspot deploy "$TARGET/$NAME.vk"
+
+echo ""
+echo "Fixtures written to $TARGET"
@@ -60,15 +60,15 @@ async function initializeCircuit() {
 }
 
 /**
- * Compute Pedersen hash by calling proof server
+ * Compute Poseidon hash by calling proof server
  *
  * This is a hybrid approach: server computes hashes (cheap operation),
  * browser generates full proof (expensive operation).
  *
  * Privacy: Server sees inputs but not the final proof structure.
- * True zero-trust would require Pedersen implementation in TypeScript.
+ * True zero-trust would require Poseidon implementation in TypeScript.
  */
-async function computePedersenHash(
+async function computePoseidonHash(
   voterSecret: bigint,
   voteChoice: number,
   proposalId: bigint
@@ -96,8 +96,8 @@ async function computePedersenHash(
       commitment: BigInt(result.voteCommitment),
     };
   } catch (error) {
-    console.error('Failed to compute Pedersen hash:', error);
-    throw new Error(`Cannot compute Pedersen hash: ${error}`);
+    console.error('Failed to compute Poseidon hash:', error);
+    throw new Error(`Cannot compute Poseidon hash: ${error}`);
   }
 }
 
@@ -119,10 +119,10 @@ export async function generateVoteProofInBrowser(
     // Initialize circuit if not already done
     const { backend, noir } = await initializeCircuit();
 
-    // Compute public inputs using REAL Pedersen hash
-    console.log("📊 Computing Pedersen hashes...");
+    // Compute public inputs using REAL Poseidon hash
+    console.log("📊 Computing Poseidon hashes...");
     const { nullifier: nullifierBigInt, commitment: commitmentBigInt } =
-      await computePedersenHash(input.voterSecret, input.voteChoice, input.proposalId);
+      await computePoseidonHash(input.voterSecret, input.voteChoice, input.proposalId);
 
     // Prepare inputs for Noir circuit
     // The keys must match the circuit parameter names
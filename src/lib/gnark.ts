@@ -71,8 +71,8 @@ export async function isProofServerAvailable(): Promise<boolean> {
  * 2. Running proof server with Sunspot installed
  * 
  * @param input Vote proof input data
- * @param nullifier Pre-computed nullifier (pedersen_hash([voterSecret, proposalId]))
- * @param voteCommitment Pre-computed commitment (pedersen_hash([voteChoice, voterSecret, proposalId]))
+ * @param nullifier Pre-computed nullifier (poseidon([voterSecret, proposalId]))
+ * @param voteCommitment Pre-computed commitment (poseidon([voteChoice, voterSecret, proposalId]))
  */
 export async function generateGnarkProof(
   input: VoteProofInput,